@@ -0,0 +1,99 @@
+use color_quant::NeuQuant;
+use gif::{Encoder, EncodingError, Frame, Repeat};
+use std::io::{self, Write};
+
+use crate::utils::base::BaseSpectrum;
+use crate::utils::render::Renderer;
+
+/// The number of colors in the shared GIF palette.
+const PALETTE_SIZE: usize = 256;
+
+/// The approximate number of pixels sampled across all frames to build the shared palette.
+const PALETTE_SAMPLE_SIZE: usize = 1 << 20;
+
+/// The `NeuQuant` sampling factor, trading palette quality (1) for speed (30).
+const QUANTIZER_SAMPLE_FACTOR: i32 = 10;
+
+/// Encodes consecutive frames of a `BaseSpectrum` as a looping animated GIF.
+///
+/// The `BaseSpectrum` itself is left untouched - frames are rendered from a copy which is ticked once per frame.
+///
+/// All frames share a single 256-color palette built from pixels sampled across the whole clip, so colors do not flicker between frames.
+/// As GIF transparency is all-or-nothing, each pixel is composited onto black according to its alpha value.
+///
+/// # Arguments
+///
+/// * `writer` - the destination of the encoded GIF.
+/// * `base` - the `BaseSpectrum` whose current state is the first frame.
+/// * `renderer` - the CPU renderer to draw each frame with.
+/// * `num_frames` - the number of frames to encode.
+/// * `frame_delay` - the delay between frames, in hundredths of a second, which must be at least 2 as browsers slow shorter delays down to 10.
+///
+/// # Errors
+///
+/// Returns an error if the `BaseSpectrum` is too large to be a GIF or the GIF cannot be written.
+pub fn write_gif<W: Write>(
+    writer: W,
+    base: &BaseSpectrum,
+    renderer: &Renderer,
+    num_frames: u32,
+    frame_delay: u16,
+) -> Result<W, EncodingError> {
+    let (Ok(width), Ok(height)) = (u16::try_from(base.width()), u16::try_from(base.height()))
+    else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "GIF dimensions cannot exceed 65535 pixels",
+        )
+        .into());
+    };
+
+    let num_pixels = base.width() as usize * base.height() as usize;
+    let mut data = vec![0_u8; num_pixels * 4];
+
+    // First pass: sample pixels from every frame to build the shared palette.
+    let sample_stride = (num_pixels * num_frames as usize / PALETTE_SAMPLE_SIZE).max(1);
+    let mut samples = Vec::with_capacity(PALETTE_SAMPLE_SIZE * 4);
+    let mut frame_base = base.clone();
+
+    for _ in 0..num_frames {
+        renderer.draw(&frame_base, &mut data);
+        for pixel in data.chunks_exact(4).step_by(sample_stride) {
+            samples.extend_from_slice(&composite(pixel));
+        }
+        frame_base.tick();
+    }
+
+    let quantizer = NeuQuant::new(QUANTIZER_SAMPLE_FACTOR, PALETTE_SIZE, &samples);
+    drop(samples);
+
+    let mut encoder = Encoder::new(writer, width, height, &quantizer.color_map_rgb())?;
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    // Second pass: render every frame again and map it onto the palette.
+    let mut indices = vec![0_u8; num_pixels];
+    let mut frame_base = base.clone();
+
+    for _ in 0..num_frames {
+        renderer.draw(&frame_base, &mut data);
+        for (index, pixel) in indices.iter_mut().zip(data.chunks_exact(4)) {
+            *index = quantizer.index_of(&composite(pixel)) as u8;
+        }
+
+        let mut frame = Frame::from_indexed_pixels(width, height, indices.as_slice(), None);
+        frame.delay = frame_delay;
+        encoder.write_frame(&frame)?;
+
+        frame_base.tick();
+    }
+
+    Ok(encoder.into_inner()?)
+}
+
+/// Composites an RGBA pixel onto a black background, returning an opaque RGBA pixel.
+fn composite(pixel: &[u8]) -> [u8; 4] {
+    let alpha = u16::from(pixel[3]);
+    let scale = |channel: u8| (u16::from(channel) * alpha / u16::from(u8::MAX)) as u8;
+
+    [scale(pixel[0]), scale(pixel[1]), scale(pixel[2]), u8::MAX]
+}
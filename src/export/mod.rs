@@ -0,0 +1,49 @@
+use std::io;
+
+use crate::utils::base::BaseSpectrum;
+use crate::utils::render::Renderer;
+
+#[cfg(feature = "gif")]
+pub mod gif;
+#[cfg(feature = "cli")]
+pub mod png;
+pub mod y4m;
+
+/// A destination for consecutive frames of RGBA pixel data.
+pub trait FrameWriter {
+    /// Writes the next frame, laid out row by row with four bytes (RGBA) per pixel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frame cannot be encoded or written.
+    fn write_frame(&mut self, data: &[u8]) -> io::Result<()>;
+}
+
+/// Renders consecutive frames of a `BaseSpectrum` into a `FrameWriter`, ticking it once after each frame.
+///
+/// # Arguments
+///
+/// * `writer` - the destination of the frames.
+/// * `base` - the `BaseSpectrum` whose current state is the first frame.
+/// * `renderer` - the CPU renderer to draw each frame with.
+/// * `num_frames` - the number of frames to write.
+///
+/// # Errors
+///
+/// Returns an error if any frame cannot be written.
+pub fn write_frames<F: FrameWriter>(
+    writer: &mut F,
+    base: &mut BaseSpectrum,
+    renderer: &Renderer,
+    num_frames: u32,
+) -> io::Result<()> {
+    let mut data = vec![0_u8; base.width() as usize * base.height() as usize * 4];
+
+    for _ in 0..num_frames {
+        renderer.draw(base, &mut data);
+        writer.write_frame(&data)?;
+        base.tick();
+    }
+
+    Ok(())
+}
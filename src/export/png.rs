@@ -0,0 +1,90 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::export::FrameWriter;
+
+/// Encodes a frame of RGBA pixel data as a PNG image.
+///
+/// # Arguments
+///
+/// * `writer` - the destination of the encoded image.
+/// * `width` - the width of the frame.
+/// * `height` - the height of the frame.
+/// * `data` - the frame's pixel data, laid out row by row with four bytes (RGBA) per pixel.
+///
+/// # Errors
+///
+/// Returns an error if the image cannot be encoded or written.
+pub fn write_png<W: Write>(
+    writer: W,
+    width: u32,
+    height: u32,
+    data: &[u8],
+) -> Result<(), png::EncodingError> {
+    let mut encoder = png::Encoder::new(writer, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(data)?;
+    writer.finish()
+}
+
+/// Writes frames as a numbered sequence of PNG images, such as `frame-00000.png`, `frame-00001.png` and so on.
+pub struct PngSequenceWriter {
+    /// The directory to write the images to.
+    directory: PathBuf,
+
+    /// The file name of each image, before its frame number.
+    stem: String,
+
+    /// The width of each frame.
+    width: u32,
+
+    /// The height of each frame.
+    height: u32,
+
+    /// The number of the next frame to be written.
+    next_frame: u32,
+}
+
+impl PngSequenceWriter {
+    /// Constructs a new `PngSequenceWriter`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - the path the images are named after - `frames/spectrum.png` results in `frames/spectrum-00000.png`, etc.
+    /// * `width` - the width of each frame.
+    /// * `height` - the height of each frame.
+    /// * `first_frame` - the number of the first frame to be written.
+    #[must_use]
+    pub fn new(path: &Path, width: u32, height: u32, first_frame: u32) -> Self {
+        Self {
+            directory: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            stem: path.file_stem().map_or_else(
+                || "spectrum".into(),
+                |stem| stem.to_string_lossy().into_owned(),
+            ),
+            width,
+            height,
+            next_frame: first_frame,
+        }
+    }
+
+    /// Returns the path of the image for the given frame number.
+    #[must_use]
+    pub fn frame_path(&self, frame: u32) -> PathBuf {
+        self.directory.join(format!("{}-{frame:05}.png", self.stem))
+    }
+}
+
+impl FrameWriter for PngSequenceWriter {
+    fn write_frame(&mut self, data: &[u8]) -> io::Result<()> {
+        let file = File::create(self.frame_path(self.next_frame))?;
+        write_png(BufWriter::new(file), self.width, self.height, data).map_err(io::Error::other)?;
+        self.next_frame += 1;
+
+        Ok(())
+    }
+}
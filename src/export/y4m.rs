@@ -0,0 +1,104 @@
+use std::io::{self, Write};
+
+use crate::export::FrameWriter;
+
+/// The scale of limited-range luma relative to full-range RGB.
+const LUMA_RANGE: f32 = 219. / 255.;
+
+/// The value of limited-range luma at black.
+const LUMA_OFFSET: f32 = 16.;
+
+/// The scale of limited-range chroma relative to full-range RGB.
+const CHROMA_RANGE: f32 = 224. / 255.;
+
+/// The value of limited-range chroma without any color.
+const CHROMA_OFFSET: f32 = 128.;
+
+/// Writes frames as an uncompressed `YUV4MPEG2` (Y4M) stream, which video encoders such as `ffmpeg` accept directly.
+///
+/// Frames are stored at full chroma resolution (`C444`) with BT.601 limited-range coefficients.
+/// As Y4M has no alpha channel, each pixel is composited onto black according to its alpha value.
+pub struct Y4mWriter<W: Write> {
+    /// The destination of the stream.
+    writer: W,
+
+    /// The width of each frame.
+    width: u32,
+
+    /// The height of each frame.
+    height: u32,
+
+    /// The luma (Y), blue-difference (U) and red-difference (V) planes of the frame being written.
+    planes: Vec<u8>,
+}
+
+impl<W: Write> Y4mWriter<W> {
+    /// Constructs a new `Y4mWriter`, writing the stream header.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - the destination of the stream.
+    /// * `width` - the width of each frame.
+    /// * `height` - the height of each frame.
+    /// * `frame_rate` - the number of frames per second.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the header cannot be written.
+    pub fn new(mut writer: W, width: u32, height: u32, frame_rate: u32) -> io::Result<Self> {
+        writeln!(
+            writer,
+            "YUV4MPEG2 W{width} H{height} F{frame_rate}:1 Ip A1:1 C444"
+        )?;
+
+        Ok(Self {
+            writer,
+            width,
+            height,
+            planes: vec![0_u8; width as usize * height as usize * 3],
+        })
+    }
+
+    /// Flushes the stream and returns the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream cannot be flushed.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write> FrameWriter for Y4mWriter<W> {
+    fn write_frame(&mut self, data: &[u8]) -> io::Result<()> {
+        let num_pixels = self.width as usize * self.height as usize;
+        if data.len() < num_pixels * 4 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "pixel buffer is too small for the Y4M frame size",
+            ));
+        }
+
+        let (y_plane, chroma_planes) = self.planes.split_at_mut(num_pixels);
+        let (u_plane, v_plane) = chroma_planes.split_at_mut(num_pixels);
+
+        for (i, pixel) in data.chunks_exact(4).take(num_pixels).enumerate() {
+            let alpha = f32::from(pixel[3]) / f32::from(u8::MAX);
+            let r = f32::from(pixel[0]) * alpha;
+            let g = f32::from(pixel[1]) * alpha;
+            let b = f32::from(pixel[2]) * alpha;
+
+            let luma = 0.114_f32.mul_add(b, 0.299_f32.mul_add(r, 0.587 * g));
+            let blue_difference = 0.5_f32.mul_add(b, (-0.168_736_f32).mul_add(r, -0.331_264 * g));
+            let red_difference = 0.5_f32.mul_add(r, (-0.418_688_f32).mul_add(g, -0.081_312 * b));
+
+            y_plane[i] = luma.mul_add(LUMA_RANGE, LUMA_OFFSET).round() as u8;
+            u_plane[i] = blue_difference.mul_add(CHROMA_RANGE, CHROMA_OFFSET).round() as u8;
+            v_plane[i] = red_difference.mul_add(CHROMA_RANGE, CHROMA_OFFSET).round() as u8;
+        }
+
+        self.writer.write_all(b"FRAME\n")?;
+        self.writer.write_all(&self.planes)
+    }
+}
@@ -1,3 +1,4 @@
+import * as wasmSpectrum from "wasm-spectrum";
 import { SpectrumWebGL, SpectrumWasm } from "wasm-spectrum";
 import { SpectrumJS } from "./spectrum";
 import { FPS } from "./utils";
@@ -7,7 +8,8 @@ const DEVICE_SCALE = window.devicePixelRatio;
 const MAX_WIDTH = document.body.clientWidth * DEVICE_SCALE;
 const MAX_HEIGHT = document.body.clientHeight * DEVICE_SCALE;
 const WEBGL_SCALE = 1;
-const WASM_SCALE = 0.4;
+const WASM_SCALE = 1;
+const WASM_RENDER_SCALE = 0.5;
 const JS_SCALE = 0.25;
 const UNIFORMS_PER_SOURCE = 4;
 
@@ -156,6 +158,39 @@ const modeStates: Record<Mode, InitialState> = {
 };
 /* eslint-enable @typescript-eslint/no-magic-numbers */
 
+/**
+ * The fraction of the canvas resolution SpectrumWasm evaluates before upsampling, raised to full resolution once rows are drawn on several threads.
+ */
+let wasmRenderScale = WASM_RENDER_SCALE;
+
+/**
+ * Starts the thread pool of a build with the `wasm-threads` feature, so SpectrumWasm draws its rows in parallel.
+ *
+ * Builds without the feature, and pages which are not cross-origin isolated, keep drawing on a single thread.
+ */
+const startThreadPool = async (): Promise<void> => {
+  const { initThreadPool } = wasmSpectrum as {
+    initThreadPool?: (numThreads: number) => Promise<void>;
+  };
+
+  if (initThreadPool !== undefined && window.crossOriginIsolated) {
+    await initThreadPool(navigator.hardwareConcurrency);
+    wasmRenderScale = 1;
+  }
+};
+
+/**
+ * Sets the render scale of a SpectrumWasm, leaving other implementations at full resolution.
+ * @param spectrum the newly created Spectrum.
+ */
+const applyRenderScale = (spectrum: Spectrum): Spectrum => {
+  if (spectrum instanceof SpectrumWasm) {
+    spectrum.setRenderScale(wasmRenderScale);
+  }
+
+  return spectrum;
+};
+
 /**
  * Contains each mode's associated implementation.
  */
@@ -206,14 +241,16 @@ const getInitialState = (mode: Mode): State => {
 
   resetParams(modeStates[mode]);
 
-  const spectrum = spectrumInitializers[mode].new(
-    width,
-    height,
-    numSources,
-    movementSpeed,
-    colorSpeed,
-    sourceDropoff,
-    mode === "webgl" ? canvasWebgl : canvas2d
+  const spectrum = applyRenderScale(
+    spectrumInitializers[mode].new(
+      width,
+      height,
+      numSources,
+      movementSpeed,
+      colorSpeed,
+      sourceDropoff,
+      mode === "webgl" ? canvasWebgl : canvas2d
+    )
   );
 
   return {
@@ -255,14 +292,16 @@ const getNewSpectrum = (): Spectrum => {
     sourceDropoff,
   } = state;
   resetParams(state);
-  return spectrumInitializers[mode].new(
-    width,
-    height,
-    numSources,
-    movementSpeed,
-    colorSpeed,
-    sourceDropoff,
-    mode === "webgl" ? canvasWebgl : canvas2d
+  return applyRenderScale(
+    spectrumInitializers[mode].new(
+      width,
+      height,
+      numSources,
+      movementSpeed,
+      colorSpeed,
+      sourceDropoff,
+      mode === "webgl" ? canvasWebgl : canvas2d
+    )
   );
 };
 
@@ -446,9 +485,10 @@ expand.addEventListener("click", () => {
   controls.classList.remove("hide-controls");
 });
 
-// Show hidden objects after setup is complete.
-state.canvas.classList.remove("hide");
-controls.classList.remove("hide");
+// Show hidden objects and start the Spectrum once the thread pool, if any, is ready for SpectrumWasm.
+void startThreadPool().then(() => {
+  state.canvas.classList.remove("hide");
+  controls.classList.remove("hide");
 
-// Start the Spectrum.
-play();
+  play();
+});
@@ -8,11 +8,29 @@
     clippy::upper_case_acronyms
 )]
 
+pub mod export;
 mod spectrum;
 mod utils;
 
+pub use spectrum::error::SpectrumError;
+pub use spectrum::scene::{decode_scene, encode_scene};
 pub use spectrum::wasm::SpectrumWasm;
 pub use spectrum::webgl::SpectrumWebGL;
+pub use spectrum::webgl2::SpectrumWebGL2;
+pub use utils::base::{BaseSpectrum, BoundaryMode, Source};
+pub use utils::color::{ColorSpace, ColorSpaceKind};
+pub use utils::kernel::{Kernel, KernelKind};
+pub use utils::palette::{Palette, PaletteError, PaletteSpace};
+pub use utils::pointer::PointerMode;
+pub use utils::render::Renderer;
+pub use utils::scene::{
+    Scene, SceneColorSpace, SceneError, SceneKernel, ScenePalette, SceneSource, MAX_SCENE_PIXELS,
+};
 
+#[cfg(feature = "wasm-threads")]
+pub use wasm_bindgen_rayon::init_thread_pool;
+
+// `wee_alloc` is not thread-safe, so threaded builds keep the default allocator.
+#[cfg(all(target_arch = "wasm32", not(feature = "wasm-threads")))]
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
@@ -0,0 +1,393 @@
+#![warn(clippy::all, clippy::pedantic, clippy::nursery)]
+
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use spectrum::export::png::{self, PngSequenceWriter};
+use spectrum::export::{self, gif, y4m::Y4mWriter};
+use spectrum::{
+    BaseSpectrum, BoundaryMode, ColorSpace, ColorSpaceKind, Kernel, KernelKind, Palette,
+    PaletteSpace, Renderer,
+};
+
+const USAGE: &str = "\
+Renders Spectrum frames to PNG files, an animated GIF or a Y4M video stream.
+
+USAGE:
+    spectrum [OPTIONS]
+
+OPTIONS:
+    --width <PIXELS>            the width of each frame [default: 1920]
+    --height <PIXELS>           the height of each frame [default: 1080]
+    --num-sources <COUNT>       the number of Sources [default: 20]
+    --movement-speed <SPEED>    the range of each Source's movement speed [default: 10]
+    --color-speed <SPEED>       the range of each Source's color speed [default: 10]
+    --source-dropoff <DROPOFF>  the rate at which the Spectrum fades out away from its Sources [default: 50]
+    --coordinates <UNITS>       `pixels`, or `normalized` to scale the Sources, their speeds and the kernel with the frame
+                                so every resolution looks like 1920x1080 [default: pixels]
+    --boundary <MODE>           the way Sources behave at the edges of the frame, one of `bounce`, `wrap`, `clamp`
+                                or `respawn` [default: bounce]
+    --loop-period <FRAMES>      makes the animation loop seamlessly after this many frames, e.g. the same as `--count`
+                                for a looping GIF, by rounding each Source's speeds [default: no loop]
+    --kernel <KIND[:PARAM]>     the distance weighting, one of `inverse-power[:EXPONENT]`, `gaussian[:SIGMA]`,
+                                `exponential[:SCALE]` or `smooth-compact[:RADIUS]` [default: inverse-power:2]
+    --color-space <KIND[:L[:C]]>
+                                the color space Hues are mapped through, one of `hsl`, `hsv` or `oklch`,
+                                optionally followed by its lightness (or value) and chroma (or saturation)
+                                [default: hsl:0.5:1]
+    --palette <COLORS>          a cyclic gradient to map Hues through instead of the color space, as comma-separated
+                                `RRGGBB` colors, each optionally followed by `@POSITION` in [0, 1)
+    --palette-space <SPACE>     the color space palette colors are blended in, one of `srgb`, `linear-rgb` or `oklab`
+                                [default: oklab]
+    --far-field-tolerance <THETA>
+                                approximates groups of Sources whose size is less than this fraction of their distance
+                                from a pixel, e.g. 0.5, which is much faster with thousands of Sources [default: exact]
+    --render-scale <SCALE>      the fraction of the resolution each frame is evaluated at before being smoothly upsampled,
+                                from 0.25 to 1 [default: 1]
+    --seed <SEED>               the seed to generate the Sources from [default: random]
+    --frame <INDEX>             the index of the first frame to render [default: 0]
+    --count <COUNT>             the number of consecutive frames to render [default: 1]
+    --fps <RATE>                the frame rate of an animated GIF or Y4M stream, at most 50 for GIFs [default: 50]
+    --output <PATH>             the PNG file to write, numbered when rendering several frames,
+                                the GIF or Y4M file to write all frames to,
+                                or `-` to stream Y4M to standard output [default: spectrum.png]
+    --help                      prints this message
+";
+
+/// The options accepted on the command line.
+struct Options {
+    width: u32,
+    height: u32,
+    num_sources: u32,
+    movement_speed: u32,
+    color_speed: u32,
+    source_dropoff: u32,
+    normalized: bool,
+    boundary_mode: BoundaryMode,
+    loop_period: Option<u32>,
+    kernel: Kernel,
+    color_space: ColorSpace,
+    palette: Option<String>,
+    palette_space: PaletteSpace,
+    far_field_tolerance: Option<f32>,
+    render_scale: f32,
+    seed: Option<u64>,
+    frame: u32,
+    count: u32,
+    fps: u32,
+    output: PathBuf,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            num_sources: 20,
+            movement_speed: 10,
+            color_speed: 10,
+            source_dropoff: 50,
+            normalized: false,
+            boundary_mode: BoundaryMode::Bounce,
+            loop_period: None,
+            kernel: Kernel::default(),
+            color_space: ColorSpace::default(),
+            palette: None,
+            palette_space: PaletteSpace::Oklab,
+            far_field_tolerance: None,
+            render_scale: 1.,
+            seed: None,
+            frame: 0,
+            count: 1,
+            fps: 50,
+            output: PathBuf::from("spectrum.png"),
+        }
+    }
+}
+
+impl Options {
+    /// Parses the Options from command line arguments, returning `None` if help was requested.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - the command line arguments, excluding the program name.
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut options = Self::default();
+
+        while let Some(flag) = args.next() {
+            if flag == "--help" || flag == "-h" {
+                return Ok(None);
+            }
+
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for `{flag}`"))?;
+
+            match flag.as_str() {
+                "--width" => options.width = parse_value(&flag, &value)?,
+                "--height" => options.height = parse_value(&flag, &value)?,
+                "--num-sources" => options.num_sources = parse_value(&flag, &value)?,
+                "--movement-speed" => options.movement_speed = parse_value(&flag, &value)?,
+                "--color-speed" => options.color_speed = parse_value(&flag, &value)?,
+                "--source-dropoff" => options.source_dropoff = parse_value(&flag, &value)?,
+                "--coordinates" => options.normalized = parse_coordinates(&value)?,
+                "--boundary" => options.boundary_mode = parse_boundary_mode(&value)?,
+                "--loop-period" => options.loop_period = Some(parse_value(&flag, &value)?),
+                "--kernel" => options.kernel = parse_kernel(&value)?,
+                "--color-space" => options.color_space = parse_color_space(&value)?,
+                "--palette" => options.palette = Some(value),
+                "--palette-space" => options.palette_space = parse_palette_space(&value)?,
+                "--far-field-tolerance" => {
+                    options.far_field_tolerance = Some(parse_value(&flag, &value)?);
+                }
+                "--render-scale" => options.render_scale = parse_value(&flag, &value)?,
+                "--seed" => options.seed = Some(parse_value(&flag, &value)?),
+                "--frame" => options.frame = parse_value(&flag, &value)?,
+                "--count" => options.count = parse_value(&flag, &value)?,
+                "--fps" => options.fps = parse_value(&flag, &value)?,
+                "--output" => options.output = PathBuf::from(value),
+                _ => return Err(format!("unknown option `{flag}`")),
+            }
+        }
+
+        if options.width == 0 || options.height == 0 {
+            return Err("`--width` and `--height` must be greater than zero".into());
+        }
+
+        if options.count == 0 {
+            return Err("`--count` must be greater than zero".into());
+        }
+
+        if options.frame.checked_add(options.count).is_none() {
+            return Err("`--frame` plus `--count` is too large".into());
+        }
+
+        if options.fps == 0 {
+            return Err("`--fps` must be greater than zero".into());
+        }
+
+        Ok(Some(options))
+    }
+}
+
+/// Parses the value passed to a command line flag.
+fn parse_value<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{value}` for `{flag}`"))
+}
+
+/// Parses whether coordinates are normalized.
+fn parse_coordinates(value: &str) -> Result<bool, String> {
+    match value {
+        "pixels" => Ok(false),
+        "normalized" => Ok(true),
+        _ => Err(format!("unknown coordinates `{value}`")),
+    }
+}
+
+/// Parses a `BoundaryMode`.
+fn parse_boundary_mode(value: &str) -> Result<BoundaryMode, String> {
+    match value {
+        "bounce" => Ok(BoundaryMode::Bounce),
+        "wrap" => Ok(BoundaryMode::Wrap),
+        "clamp" => Ok(BoundaryMode::Clamp),
+        "respawn" => Ok(BoundaryMode::Respawn),
+        _ => Err(format!("unknown boundary mode `{value}`")),
+    }
+}
+
+/// Parses a `Kernel` written as its kind, optionally followed by a colon and its parameter.
+fn parse_kernel(value: &str) -> Result<Kernel, String> {
+    let (kind, parameter) = match value.split_once(':') {
+        Some((kind, parameter)) => (kind, Some(parse_value("--kernel", parameter)?)),
+        None => (value, None),
+    };
+
+    let kind = match kind {
+        "inverse-power" => KernelKind::InversePower,
+        "gaussian" => KernelKind::Gaussian,
+        "exponential" => KernelKind::Exponential,
+        "smooth-compact" => KernelKind::SmoothCompact,
+        _ => return Err(format!("unknown kernel `{kind}`")),
+    };
+
+    Ok(Kernel::new(kind, parameter))
+}
+
+/// Parses a `ColorSpace` written as its kind, optionally followed by colon-separated lightness and chroma.
+fn parse_color_space(value: &str) -> Result<ColorSpace, String> {
+    let mut parts = value.split(':');
+
+    let kind = match parts.next().unwrap_or_default() {
+        "hsl" => ColorSpaceKind::Hsl,
+        "hsv" => ColorSpaceKind::Hsv,
+        "oklch" => ColorSpaceKind::Oklch,
+        kind => return Err(format!("unknown color space `{kind}`")),
+    };
+    let lightness = parts
+        .next()
+        .map(|lightness| parse_value("--color-space", lightness))
+        .transpose()?;
+    let chroma = parts
+        .next()
+        .map(|chroma| parse_value("--color-space", chroma))
+        .transpose()?;
+
+    if parts.next().is_some() {
+        return Err(format!("invalid value `{value}` for `--color-space`"));
+    }
+
+    Ok(ColorSpace::new(kind, lightness, chroma))
+}
+
+/// Parses a color space for palette blending.
+fn parse_palette_space(value: &str) -> Result<PaletteSpace, String> {
+    match value {
+        "srgb" => Ok(PaletteSpace::Srgb),
+        "linear-rgb" => Ok(PaletteSpace::LinearRgb),
+        "oklab" => Ok(PaletteSpace::Oklab),
+        _ => Err(format!("unknown palette space `{value}`")),
+    }
+}
+
+/// Parses a `Palette` written as comma-separated `RRGGBB` colors, each optionally followed by `@POSITION`.
+fn parse_palette(value: &str, space: PaletteSpace) -> Result<Palette, String> {
+    let mut colors = Vec::new();
+    let mut positions = Vec::new();
+
+    for stop in value.split(',') {
+        let (color, position) = match stop.split_once('@') {
+            Some((color, position)) => (color, Some(parse_value::<f32>("--palette", position)?)),
+            None => (stop, None),
+        };
+
+        let color = color.trim().trim_start_matches('#');
+        if color.len() != 6 {
+            return Err(format!("invalid color `{color}` for `--palette`"));
+        }
+        colors.push(
+            u32::from_str_radix(color, 16)
+                .map_err(|_| format!("invalid color `{color}` for `--palette`"))?,
+        );
+        positions.extend(position);
+    }
+
+    let positions = (!positions.is_empty()).then_some(positions.as_slice());
+    Palette::new(&colors, positions, space).map_err(|err| err.to_string())
+}
+
+fn run(options: &Options) -> Result<(), Box<dyn Error>> {
+    let mut base = BaseSpectrum::new(
+        options.width,
+        options.height,
+        options.num_sources,
+        options.movement_speed,
+        options.color_speed,
+        options.seed,
+    );
+    base.set_normalized(options.normalized);
+    base.set_boundary_mode(options.boundary_mode);
+    base.set_loop_period(options.loop_period);
+    let mut renderer = Renderer::new(options.source_dropoff);
+    renderer.set_kernel(options.kernel);
+    renderer.set_color_space(options.color_space);
+    renderer.set_far_field_tolerance(options.far_field_tolerance);
+    renderer.set_render_scale(options.render_scale);
+    if let Some(palette) = &options.palette {
+        renderer.set_palette(Some(parse_palette(palette, options.palette_space)?));
+    }
+
+    eprintln!("seed: {}", base.seed());
+
+    base.seek(f64::from(options.frame));
+
+    let extension = options
+        .output
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("gif") => {
+            // GIF delays are in hundredths of a second, and browsers slow delays below 2 down to 10.
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let frame_delay = (100. / f64::from(options.fps)).round().max(2.) as u16;
+            gif::write_gif(
+                BufWriter::new(File::create(&options.output)?),
+                &base,
+                &renderer,
+                options.count,
+                frame_delay,
+            )?;
+        }
+        Some("y4m") => {
+            let file = BufWriter::new(File::create(&options.output)?);
+            let mut writer = Y4mWriter::new(file, options.width, options.height, options.fps)?;
+            export::write_frames(&mut writer, &mut base, &renderer, options.count)?;
+            writer.finish()?;
+        }
+        _ if options.output == Path::new("-") => {
+            let stdout = BufWriter::new(io::stdout().lock());
+            let mut writer = Y4mWriter::new(stdout, options.width, options.height, options.fps)?;
+            export::write_frames(&mut writer, &mut base, &renderer, options.count)?;
+            writer.finish()?;
+        }
+        _ if options.count == 1 => {
+            let mut data = vec![0_u8; options.width as usize * options.height as usize * 4];
+            renderer.draw(&base, &mut data);
+            png::write_png(
+                BufWriter::new(File::create(&options.output)?),
+                options.width,
+                options.height,
+                &data,
+            )?;
+        }
+        _ => {
+            let mut writer = PngSequenceWriter::new(
+                &options.output,
+                options.width,
+                options.height,
+                options.frame,
+            );
+            export::write_frames(&mut writer, &mut base, &renderer, options.count)?;
+            eprintln!(
+                "wrote {} to {}",
+                writer.frame_path(options.frame).display(),
+                writer
+                    .frame_path(options.frame + options.count - 1)
+                    .display()
+            );
+
+            return Ok(());
+        }
+    }
+
+    if options.output != Path::new("-") {
+        eprintln!("wrote {}", options.output.display());
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{USAGE}");
+            return;
+        }
+        Err(message) => {
+            eprintln!("error: {message}\n\n{USAGE}");
+            process::exit(2);
+        }
+    };
+
+    if let Err(error) = run(&options) {
+        eprintln!("error: {error}");
+        process::exit(1);
+    }
+}
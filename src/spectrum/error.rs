@@ -0,0 +1,137 @@
+use std::error::Error;
+use std::fmt;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::utils::palette::PaletteError;
+use crate::utils::scene::SceneError;
+
+/// An error encountered while setting up, editing, drawing or exporting a Spectrum.
+///
+/// When returned to JavaScript it becomes an `Error` whose `name` identifies the kind of error, such as `NoContextError`, so front-ends can fall back to another Spectrum implementation.
+#[derive(Debug)]
+pub enum SpectrumError {
+    /// The canvas does not provide the named rendering context.
+    NoContext(&'static str),
+
+    /// The rendering context was lost, so no `WebGL` objects can be created.
+    ContextLost,
+
+    /// A shader failed to compile, with the compiler's info log.
+    ShaderCompile(String),
+
+    /// The shader program failed to link, with the linker's info log.
+    ProgramLink(String),
+
+    /// The device cannot pass this many Sources to the fragment shader.
+    TooManySources {
+        /// The number of Sources requested.
+        sources: u32,
+
+        /// The largest number of Sources the device supports.
+        max: u32,
+    },
+
+    /// A scene could not be restored.
+    InvalidScene(SceneError),
+
+    /// A palette could not be built.
+    InvalidPalette(PaletteError),
+
+    /// There is no Source at the given index.
+    NoSuchSource(u32),
+
+    /// The pixel data could not be put onto the canvas, with the browser's message.
+    Draw(String),
+
+    /// The Spectrum could not be exported, with the encoder's message.
+    Export(String),
+}
+
+impl SpectrumError {
+    /// Returns the `name` of the JavaScript `Error` the `SpectrumError` is converted into.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::NoContext(_) => "NoContextError",
+            Self::ContextLost => "ContextLostError",
+            Self::ShaderCompile(_) => "ShaderCompileError",
+            Self::ProgramLink(_) => "ProgramLinkError",
+            Self::TooManySources { .. } => "TooManySourcesError",
+            Self::InvalidScene(_) => "InvalidSceneError",
+            Self::InvalidPalette(_) => "InvalidPaletteError",
+            Self::NoSuchSource(_) => "NoSuchSourceError",
+            Self::Draw(_) => "DrawError",
+            Self::Export(_) => "ExportError",
+        }
+    }
+
+    /// Constructs a `SpectrumError::Draw` from an exception thrown by the browser.
+    ///
+    /// # Arguments
+    ///
+    /// * `err` - the exception, usually a `DOMException`.
+    #[must_use]
+    pub fn draw(err: &JsValue) -> Self {
+        let message = err
+            .dyn_ref::<js_sys::Error>()
+            .map(|err| String::from(err.message()))
+            .or_else(|| err.as_string())
+            .unwrap_or_else(|| format!("{err:?}"));
+
+        Self::Draw(message)
+    }
+}
+
+impl fmt::Display for SpectrumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoContext(context) => {
+                write!(f, "the canvas does not provide a `{context}` context")
+            }
+            Self::ContextLost => write!(f, "the rendering context was lost"),
+            Self::ShaderCompile(log) => write!(f, "a shader failed to compile: {log}"),
+            Self::ProgramLink(log) => write!(f, "the shader program failed to link: {log}"),
+            Self::TooManySources { sources, max } => write!(
+                f,
+                "{sources} sources were requested, but the device supports at most {max}"
+            ),
+            Self::InvalidScene(err) => err.fmt(f),
+            Self::InvalidPalette(err) => write!(f, "invalid palette: {err}"),
+            Self::NoSuchSource(index) => write!(f, "no source at index {index}"),
+            Self::Draw(message) => write!(f, "the Spectrum could not be drawn: {message}"),
+            Self::Export(message) => write!(f, "the Spectrum could not be exported: {message}"),
+        }
+    }
+}
+
+impl Error for SpectrumError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::InvalidScene(err) => Some(err),
+            Self::InvalidPalette(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<SceneError> for SpectrumError {
+    fn from(err: SceneError) -> Self {
+        Self::InvalidScene(err)
+    }
+}
+
+impl From<PaletteError> for SpectrumError {
+    fn from(err: PaletteError) -> Self {
+        Self::InvalidPalette(err)
+    }
+}
+
+impl From<SpectrumError> for JsValue {
+    fn from(err: SpectrumError) -> Self {
+        let error = js_sys::Error::new(&err.to_string());
+        error.set_name(err.name());
+
+        error.into()
+    }
+}
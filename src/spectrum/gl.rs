@@ -0,0 +1,648 @@
+use crate::utils::palette::Palette;
+use crate::utils::render::Renderer;
+use crate::utils::upsample;
+
+/// The version of GLSL ES a `WebGL` context compiles, and the parts of the Spectrum's shaders which differ between versions.
+#[derive(Clone, Copy)]
+pub enum Glsl {
+    /// GLSL ES 1.00, compiled by `WebGL` contexts.
+    Es1,
+
+    /// GLSL ES 3.00, compiled by `WebGL2` contexts.
+    Es3,
+}
+
+impl Glsl {
+    /// Returns the line a shader starts with, declaring its version.
+    const fn version(self) -> &'static str {
+        match self {
+            Self::Es1 => "",
+            Self::Es3 => "#version 300 es",
+        }
+    }
+
+    /// Returns the vertex shader drawing a fragment shader over the whole viewport.
+    #[must_use]
+    pub const fn vertex_shader(self) -> &'static str {
+        match self {
+            Self::Es1 => {
+                r"
+            attribute vec4 a_position;
+
+            void main(void) {
+                gl_Position = a_position;
+            }
+        "
+            }
+            Self::Es3 => {
+                r"#version 300 es
+            in vec4 a_position;
+
+            void main(void) {
+                gl_Position = a_position;
+            }
+        "
+            }
+        }
+    }
+
+    /// Returns the fragment shader upsampling the frame texture to the canvas.
+    #[must_use]
+    pub const fn upsample_shader(self) -> &'static str {
+        match self {
+            Self::Es1 => upsample::GLSL_ES_1,
+            Self::Es3 => upsample::GLSL_ES_3,
+        }
+    }
+
+    /// Returns the body of `hue_to_rgb` sampling a `Palette`.
+    const fn palette(self) -> &'static str {
+        match self {
+            Self::Es1 => Palette::GLSL,
+            Self::Es3 => Palette::GLSL_ES_3,
+        }
+    }
+
+    /// Returns the declaration of the fragment shader's output color, and the variable it is written to.
+    const fn output(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Es1 => ("", "gl_FragColor"),
+            Self::Es3 => ("out vec4 frag_color;", "frag_color"),
+        }
+    }
+}
+
+/// The number of uniform vectors the Spectrum's fragment shader uses besides its Sources.
+///
+/// `source_dropoff`, `height`, `pixel_size`, `kernel_parameter`, `color_lightness`, `color_chroma` and `palette` are each counted as a whole vector, as devices are not required to pack them together.
+pub const OTHER_UNIFORM_VECTORS: u32 = 7;
+
+/// Returns the Spectrum's fragment shader.
+///
+/// # Arguments
+///
+/// * `glsl` - the version of GLSL ES to write the shader in.
+/// * `renderer` - the `Renderer` whose kinds of `Kernel` and `ColorSpace`, and whether it has a `Palette`, the shader uses.
+/// * `source_declaration` - the GLSL declaring the shader's Sources.
+/// * `source_loop` - the head of the loop reading each Source into `vec4 source`.
+pub fn get_shader_source(
+    glsl: Glsl,
+    renderer: &Renderer,
+    source_declaration: &str,
+    source_loop: &str,
+) -> String {
+    let version = glsl.version();
+    let kernel_weight = renderer.kernel().kind().glsl();
+    let hue_to_rgb = if renderer.palette().is_some() {
+        glsl.palette()
+    } else {
+        renderer.color_space().kind().glsl()
+    };
+    let (output_declaration, output) = glsl.output();
+
+    format!(
+        r"{version}
+            #define PI 3.141592653589793
+            #define TWO_PI 6.283185307179586
+            #define PI_4 0.7853981633974483
+            #define PI_3 1.0471975511965976
+            #define PI_2 1.5707963267948966
+            #define PI_3_2 4.71238898038469
+
+            precision highp float;
+{source_declaration}
+            uniform float source_dropoff;
+            uniform float height;
+            uniform float pixel_size;
+            uniform float kernel_parameter;
+            uniform float color_lightness;
+            uniform float color_chroma;
+            uniform sampler2D palette;
+
+            {output_declaration}
+
+            float atan_approx(float quotient) {{
+                return (PI_4 + 0.273 * (1.0 - abs(quotient))) * quotient;
+            }}
+
+            float atan2_approx(float x, float y) {{
+                if (abs(x) > abs(y)) {{
+                    if (x < 0.0) {{
+                        return atan_approx(y / x) + PI;
+                    }} else if (y < 0.0) {{
+                        return atan_approx(y / x) + TWO_PI;
+                    }} else {{
+                        return atan_approx(y / x);
+                    }}
+                }} else if (y < 0.0) {{
+                    return PI_3_2 - atan_approx(x / y);
+                }} else {{
+                    return PI_2 - atan_approx(x / y);
+                }}
+            }}
+
+            float kernel_weight(float dist_sq) {{
+                {kernel_weight}
+            }}
+
+            vec3 pure_hue(float hue) {{
+                return clamp(abs(mod(hue / PI_3 + vec3(0.0, 4.0, 2.0), 6.0) - 3.0) - 1.0, 0.0, 1.0);
+            }}
+
+            vec3 hue_to_rgb(float hue) {{
+                {hue_to_rgb}
+            }}
+
+            void main() {{
+                // gl_FragCoord is the center of a pixel and counts rows up from the bottom, while the CPU renderer evaluates pixel (x, y) at (x, y), counting rows down from the top.
+                float x = (gl_FragCoord[0] - 0.5) * pixel_size;
+                float y = (height - 0.5 - gl_FragCoord[1]) * pixel_size;
+                float cos_sum = 0.0;
+                float sin_sum = 0.0;
+                float dist_factor_inverse_sum = 0.0;
+{source_loop}
+                    float dist_factor_inverse = kernel_weight(pow(source.x - x, 2.0) + pow(source.y - y, 2.0));
+                    cos_sum += source.z * dist_factor_inverse;
+                    sin_sum += source.w * dist_factor_inverse;
+
+                    dist_factor_inverse_sum += dist_factor_inverse;
+                }}
+
+                if (dist_factor_inverse_sum > 1.0) {{
+                    dist_factor_inverse_sum = 1.0;
+                }}
+
+                float alpha_factor = pow(dist_factor_inverse_sum, source_dropoff);
+
+                // Like math::atan2_approx, the cosine is the x component and the sine the y component.
+                float hue = atan2_approx(cos_sum, sin_sum);
+
+                {output} = vec4(hue_to_rgb(hue) * alpha_factor, 1.0);
+            }}
+        "
+    )
+}
+
+/// Defines the functions setting up `WebGL` objects, which are the same for every version of `WebGL` apart from the type of their context.
+///
+/// # Arguments
+///
+/// * `$context` - the type of the `WebGL` context, such as `WebGlRenderingContext`.
+/// * `$glsl` - the `Glsl` version the context compiles.
+macro_rules! gl_helpers {
+    ($context:ident, $glsl:expr) => {
+        /// Compiles and links a shader program drawing a fragment shader over the whole viewport, makes it the context's current program and binds its vertex attribute to the current array buffer.
+        ///
+        /// # Parameters
+        ///
+        /// * `context` - the `WebGL` context.
+        /// * `fragment_source` - the GLSL fragment shader source.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error, leaving the context's current program in place, if a shader fails to compile or the program fails to link.
+        fn link_program(
+            context: &$context,
+            fragment_source: &str,
+        ) -> Result<web_sys::WebGlProgram, $crate::spectrum::error::SpectrumError> {
+            let vertex_shader =
+                compile_shader(context, $context::VERTEX_SHADER, $glsl.vertex_shader())?;
+
+            let fragment_shader =
+                compile_shader(context, $context::FRAGMENT_SHADER, fragment_source)?;
+
+            let program = context
+                .create_program()
+                .ok_or($crate::spectrum::error::SpectrumError::ContextLost)?;
+
+            context.attach_shader(&program, &vertex_shader);
+            context.attach_shader(&program, &fragment_shader);
+            context.link_program(&program);
+
+            if !context
+                .get_program_parameter(&program, $context::LINK_STATUS)
+                .as_bool()
+                .unwrap_or(false)
+            {
+                let log = context.get_program_info_log(&program).unwrap_or_default();
+                context.delete_program(Some(&program));
+
+                return Err($crate::spectrum::error::SpectrumError::ProgramLink(log));
+            }
+
+            context.use_program(Some(&program));
+
+            let position_attribute_loc = context.get_attrib_location(&program, "a_position") as u32;
+
+            context.enable_vertex_attrib_array(position_attribute_loc);
+
+            context.vertex_attrib_pointer_with_i32(
+                position_attribute_loc,
+                2,
+                $context::FLOAT,
+                false,
+                0,
+                0,
+            );
+
+            Ok(program)
+        }
+
+        /// Creates a texture bound to a texture unit, with a filter and a horizontal wrapping mode.
+        ///
+        /// # Parameters
+        ///
+        /// * `context` - the `WebGL` context.
+        /// * `unit` - the texture unit to bind the texture to.
+        /// * `filter` - the minification and magnification filter.
+        /// * `wrap_s` - the horizontal wrapping mode, while the texture is clamped vertically.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the context was lost.
+        fn create_texture(
+            context: &$context,
+            unit: u32,
+            filter: u32,
+            wrap_s: u32,
+        ) -> Result<web_sys::WebGlTexture, $crate::spectrum::error::SpectrumError> {
+            let texture = context
+                .create_texture()
+                .ok_or($crate::spectrum::error::SpectrumError::ContextLost)?;
+            context.active_texture(unit);
+            context.bind_texture($context::TEXTURE_2D, Some(&texture));
+
+            for (parameter, value) in [
+                ($context::TEXTURE_MIN_FILTER, filter),
+                ($context::TEXTURE_MAG_FILTER, filter),
+                ($context::TEXTURE_WRAP_S, wrap_s),
+                ($context::TEXTURE_WRAP_T, $context::CLAMP_TO_EDGE),
+            ] {
+                context.tex_parameteri($context::TEXTURE_2D, parameter, value.cast_signed());
+            }
+
+            Ok(texture)
+        }
+
+        /// Creates the framebuffer the Spectrum is drawn into below a render scale of 1, drawing to the frame texture.
+        ///
+        /// # Parameters
+        ///
+        /// * `context` - the `WebGL` context.
+        /// * `frame_texture` - the texture the framebuffer draws to.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the context was lost.
+        fn create_framebuffer(
+            context: &$context,
+            frame_texture: &web_sys::WebGlTexture,
+        ) -> Result<web_sys::WebGlFramebuffer, $crate::spectrum::error::SpectrumError> {
+            let framebuffer = context
+                .create_framebuffer()
+                .ok_or($crate::spectrum::error::SpectrumError::ContextLost)?;
+            context.bind_framebuffer($context::FRAMEBUFFER, Some(&framebuffer));
+            context.framebuffer_texture_2d(
+                $context::FRAMEBUFFER,
+                $context::COLOR_ATTACHMENT0,
+                $context::TEXTURE_2D,
+                Some(frame_texture),
+                0,
+            );
+            context.bind_framebuffer($context::FRAMEBUFFER, None);
+
+            Ok(framebuffer)
+        }
+
+        /// Compiles a `WebGL` shader from source.
+        ///
+        /// Obtained from the [`wasm-bindgen` Guide `WebGL` example](https://rustwasm.github.io/wasm-bindgen/examples/webgl.html).
+        ///
+        /// # Parameters
+        ///
+        /// * `context` - the `WebGL` context.
+        /// * `shader_type` - the shader's type - vertex or fragment shader.
+        /// * `source` - the GLSL shader source.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error with the compiler's info log if the shader fails to compile.
+        fn compile_shader(
+            context: &$context,
+            shader_type: u32,
+            source: &str,
+        ) -> Result<web_sys::WebGlShader, $crate::spectrum::error::SpectrumError> {
+            let shader = context
+                .create_shader(shader_type)
+                .ok_or($crate::spectrum::error::SpectrumError::ContextLost)?;
+            context.shader_source(&shader, source);
+            context.compile_shader(&shader);
+
+            if !context
+                .get_shader_parameter(&shader, $context::COMPILE_STATUS)
+                .as_bool()
+                .unwrap_or(false)
+            {
+                let log = context.get_shader_info_log(&shader).unwrap_or_default();
+                context.delete_shader(Some(&shader));
+
+                return Err($crate::spectrum::error::SpectrumError::ShaderCompile(log));
+            }
+
+            Ok(shader)
+        }
+    };
+}
+
+/// Implements the methods shared by the `WebGL` Spectrum implementations, which keep their shader program and textures in step with the `Renderer`.
+///
+/// The implementation needs `context`, `palette_texture`, `framebuffer`, `frame_texture` and `upsample_program` fields alongside those needed by `spectrum_methods`, and `draw` and `rebuild_program` methods.
+///
+/// # Arguments
+///
+/// * `$spectrum` - the Spectrum implementation.
+/// * `$context` - the type of its `WebGL` context, such as `WebGlRenderingContext`.
+/// * `$frame_format` - the internal format of the frame texture.
+macro_rules! gl_spectrum_methods {
+    ($spectrum:ident, $context:ident, $frame_format:expr) => {
+        #[wasm_bindgen::prelude::wasm_bindgen]
+        impl $spectrum {
+            /// Switches the `Kernel` weighting each Source by its distance from a pixel.
+            ///
+            /// The shader program is only rebuilt if the kind of `Kernel` changes.
+            ///
+            /// # Arguments
+            ///
+            /// * `kind` - the kind of `Kernel`.
+            /// * `parameter` - the exponent, standard deviation, scale or radius of the `Kernel`, or `undefined` to use the kind's default.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the shader program cannot be rebuilt.
+            #[allow(non_snake_case)]
+            pub fn setKernel(
+                &mut self,
+                kind: $crate::utils::kernel::KernelKind,
+                parameter: Option<f32>,
+            ) -> Result<(), wasm_bindgen::JsValue> {
+                let rebuild = kind != self.renderer.kernel().kind();
+                self.renderer
+                    .set_kernel($crate::utils::kernel::Kernel::new(kind, parameter));
+
+                if rebuild {
+                    self.rebuild_program()?;
+                }
+
+                Ok(())
+            }
+
+            /// Switches the color space each pixel's Hue is mapped through.
+            ///
+            /// The shader program is only rebuilt if the kind of color space changes.
+            ///
+            /// # Arguments
+            ///
+            /// * `kind` - the kind of color space.
+            /// * `lightness` - the HSL lightness, HSV value or Oklab lightness, or `undefined` to use the kind's default.
+            /// * `chroma` - the HSL or HSV saturation or the Oklab chroma, or `undefined` to use the kind's default.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the shader program cannot be rebuilt.
+            #[allow(non_snake_case)]
+            pub fn setColorSpace(
+                &mut self,
+                kind: $crate::utils::color::ColorSpaceKind,
+                lightness: Option<f32>,
+                chroma: Option<f32>,
+            ) -> Result<(), wasm_bindgen::JsValue> {
+                let rebuild = kind != self.renderer.color_space().kind();
+                self.renderer
+                    .set_color_space($crate::utils::color::ColorSpace::new(
+                        kind, lightness, chroma,
+                    ));
+
+                if rebuild {
+                    self.rebuild_program()?;
+                }
+
+                Ok(())
+            }
+
+            /// Maps each pixel's Hue through a cyclic gradient instead of the color space.
+            ///
+            /// # Arguments
+            ///
+            /// * `colors` - the color of each of the gradient's stops, as `0xRRGGBB`.
+            /// * `positions` - the position of each stop in [0, 1), or `undefined` to space the stops evenly.
+            /// * `space` - the color space stops are interpolated in, or `undefined` for Oklab.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if there are no colors, the positions do not match the colors, or the palette texture or shader program cannot be rebuilt.
+            #[allow(non_snake_case, clippy::needless_pass_by_value)]
+            pub fn setPalette(
+                &mut self,
+                colors: &[u32],
+                positions: Option<Vec<f32>>,
+                space: Option<$crate::utils::palette::PaletteSpace>,
+            ) -> Result<(), wasm_bindgen::JsValue> {
+                let palette = $crate::utils::palette::Palette::new(
+                    colors,
+                    positions.as_deref(),
+                    space.unwrap_or($crate::utils::palette::PaletteSpace::Oklab),
+                )
+                .map_err($crate::spectrum::error::SpectrumError::InvalidPalette)?;
+
+                let rebuild = self.renderer.palette().is_none();
+                self.renderer.set_palette(Some(palette));
+                self.upload_palette()?;
+
+                if rebuild {
+                    self.rebuild_program()?;
+                }
+
+                Ok(())
+            }
+
+            /// Stops mapping each pixel's Hue through a gradient, returning to the color space.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the shader program cannot be rebuilt.
+            #[allow(non_snake_case)]
+            pub fn clearPalette(&mut self) -> Result<(), wasm_bindgen::JsValue> {
+                if self.renderer.palette().is_some() {
+                    self.renderer.set_palette(None);
+                    self.rebuild_program()?;
+                }
+
+                Ok(())
+            }
+
+            /// Sets the fraction of the canvas resolution the Spectrum is drawn at before being upsampled to the canvas.
+            ///
+            /// As the Spectrum is smooth, a scale of 0.5 is nearly indistinguishable from full resolution while shading a quarter as many pixels.
+            ///
+            /// # Arguments
+            ///
+            /// * `render_scale` - the fraction of the resolution, clamped to [0.25, 1].
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the frame texture cannot be resized.
+            #[allow(non_snake_case)]
+            pub fn setRenderScale(
+                &mut self,
+                render_scale: f32,
+            ) -> Result<(), wasm_bindgen::JsValue> {
+                self.renderer.set_render_scale(render_scale);
+
+                Ok(self.allocate_frame_texture()?)
+            }
+
+            /// Resizes the Spectrum and its canvas, keeping the same artwork by moving each source to the same relative position.
+            ///
+            /// The `WebGL` viewport is updated to the new size, and since resizing the canvas clears it, the Spectrum is redrawn immediately.
+            ///
+            /// # Arguments
+            ///
+            /// * `width` - the new width of the Spectrum.
+            /// * `height` - the new height of the Spectrum.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the frame texture cannot be resized or the resized Spectrum cannot be drawn.
+            pub fn resize(
+                &mut self,
+                width: u32,
+                height: u32,
+            ) -> Result<(), $crate::spectrum::error::SpectrumError> {
+                self.base.resize(width, height);
+
+                if let Some(canvas) = self.context.canvas().and_then(|canvas| {
+                    wasm_bindgen::JsCast::dyn_into::<web_sys::HtmlCanvasElement>(canvas).ok()
+                }) {
+                    canvas.set_width(width);
+                    canvas.set_height(height);
+                }
+                self.context
+                    .viewport(0, 0, width.cast_signed(), height.cast_signed());
+                self.allocate_frame_texture()?;
+
+                self.draw()
+            }
+        }
+
+        impl $spectrum {
+            /// Uploads the `Renderer`'s `Palette`, if it has one, to the palette texture.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the browser rejects the upload.
+            fn upload_palette(&self) -> Result<(), $crate::spectrum::error::SpectrumError> {
+                if let Some(palette) = self.renderer.palette() {
+                    self.context.active_texture($context::TEXTURE0);
+                    self.context
+                        .bind_texture($context::TEXTURE_2D, Some(&self.palette_texture));
+                    self.context
+                        .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+                            $context::TEXTURE_2D,
+                            0,
+                            $context::RGB.cast_signed(),
+                            ($crate::utils::palette::PALETTE_SIZE as u32).cast_signed(),
+                            1,
+                            0,
+                            $context::RGB,
+                            $context::UNSIGNED_BYTE,
+                            Some(palette.bytes()),
+                        )
+                        .map_err(|err| $crate::spectrum::error::SpectrumError::draw(&err))?;
+                }
+
+                Ok(())
+            }
+
+            /// Returns the size of the frame texture the Spectrum is drawn into below a render scale of 1, which holds the same samples as the CPU renderer draws.
+            fn frame_size(&self) -> (u32, u32) {
+                let render_scale = self.renderer.render_scale();
+                let scale = |len: u32| {
+                    ($crate::utils::upsample::scaled_len(len as usize, render_scale) as u32).max(1)
+                };
+
+                (scale(self.base.width()), scale(self.base.height()))
+            }
+
+            /// Resizes the frame texture to match the canvas and render scale, if the Spectrum is drawn below a render scale of 1.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the browser rejects the allocation.
+            fn allocate_frame_texture(&self) -> Result<(), $crate::spectrum::error::SpectrumError> {
+                if self.renderer.render_scale() >= 1. {
+                    return Ok(());
+                }
+
+                let (width, height) = self.frame_size();
+                let context = &self.context;
+
+                context.active_texture($context::TEXTURE2);
+                context.bind_texture($context::TEXTURE_2D, Some(&self.frame_texture));
+                context
+                    .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+                        $context::TEXTURE_2D,
+                        0,
+                        $frame_format.cast_signed(),
+                        width.cast_signed(),
+                        height.cast_signed(),
+                        0,
+                        $context::RGBA,
+                        $context::UNSIGNED_BYTE,
+                        None,
+                    )
+                    .map_err(|err| $crate::spectrum::error::SpectrumError::draw(&err))?;
+                context.active_texture($context::TEXTURE0);
+
+                Ok(())
+            }
+
+            /// Upsamples the frame texture to the canvas, leaving the canvas as the render target.
+            fn draw_upsampled(&self) {
+                let (width, height) = self.frame_size();
+                let context = &self.context;
+
+                context.bind_framebuffer($context::FRAMEBUFFER, None);
+                context.viewport(
+                    0,
+                    0,
+                    self.base.width().cast_signed(),
+                    self.base.height().cast_signed(),
+                );
+                context.use_program(Some(&self.upsample_program));
+
+                context.uniform1f(
+                    context
+                        .get_uniform_location(&self.upsample_program, "render_scale")
+                        .as_ref(),
+                    self.renderer.render_scale(),
+                );
+                context.uniform1f(
+                    context
+                        .get_uniform_location(&self.upsample_program, "height")
+                        .as_ref(),
+                    self.base.height() as f32,
+                );
+                // GLSL ES 3.00 reads the size with `textureSize` instead, so has no such uniform.
+                context.uniform2f(
+                    context
+                        .get_uniform_location(&self.upsample_program, "frame_size")
+                        .as_ref(),
+                    width as f32,
+                    height as f32,
+                );
+
+                context.draw_arrays($context::TRIANGLE_FAN, 0, 4);
+            }
+        }
+    };
+}
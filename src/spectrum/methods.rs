@@ -0,0 +1,267 @@
+/// Implements the methods shared by every Spectrum implementation, which only delegate to its `BaseSpectrum` and `Renderer`.
+///
+/// The implementation needs `base` and `renderer` fields, and a `from_parts` constructor setting up a Spectrum from them on a canvas.
+///
+/// # Arguments
+///
+/// * `$spectrum` - the Spectrum implementation.
+macro_rules! spectrum_methods {
+    ($spectrum:ident) => {
+        #[wasm_bindgen::prelude::wasm_bindgen]
+        impl $spectrum {
+            /// Restores a Spectrum from a JSON scene produced by `toJSON`.
+            ///
+            /// # Arguments
+            ///
+            /// * `json` - the JSON scene.
+            /// * `canvas` - the canvas to draw the Spectrum to.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the JSON is not a valid scene, or the Spectrum cannot be set up on the canvas.
+            #[allow(non_snake_case)]
+            pub fn fromJSON(
+                json: &str,
+                canvas: &web_sys::HtmlCanvasElement,
+            ) -> Result<Self, wasm_bindgen::JsValue> {
+                let (base, renderer) = $crate::utils::scene::Scene::from_json(json)
+                    .map_err($crate::spectrum::error::SpectrumError::InvalidScene)?
+                    .restore();
+
+                Ok(Self::from_parts(base, renderer, canvas)?)
+            }
+
+            /// Serializes the Spectrum's current state to a JSON scene, which can be restored with `fromJSON` in any Spectrum implementation.
+            #[must_use]
+            #[allow(non_snake_case)]
+            pub fn toJSON(&self) -> String {
+                $crate::utils::scene::Scene::capture(&self.base, &self.renderer).to_json()
+            }
+
+            /// Restores a Spectrum from a compact scene code produced by `toCode`.
+            ///
+            /// # Arguments
+            ///
+            /// * `code` - the scene code.
+            /// * `canvas` - the canvas to draw the Spectrum to.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the string is not a valid scene code, or the Spectrum cannot be set up on the canvas.
+            #[allow(non_snake_case)]
+            pub fn fromCode(
+                code: &str,
+                canvas: &web_sys::HtmlCanvasElement,
+            ) -> Result<Self, wasm_bindgen::JsValue> {
+                let (base, renderer) = $crate::utils::scene::Scene::from_code(code)
+                    .map_err($crate::spectrum::error::SpectrumError::InvalidScene)?
+                    .restore();
+
+                Ok(Self::from_parts(base, renderer, canvas)?)
+            }
+
+            /// Encodes the Spectrum's current state as a compact, URL-safe scene code, which can be restored with `fromCode` in any Spectrum implementation.
+            #[must_use]
+            #[allow(non_snake_case)]
+            pub fn toCode(&self) -> String {
+                $crate::utils::scene::Scene::capture(&self.base, &self.renderer).to_code()
+            }
+
+            #[allow(non_snake_case)]
+            pub fn updateMovementSpeed(&mut self, movement_speed: u32) {
+                self.base.update_movement_speed(movement_speed);
+            }
+
+            #[allow(non_snake_case)]
+            pub fn updateColorSpeed(&mut self, color_speed: u32) {
+                self.base.update_color_speed(color_speed);
+            }
+
+            #[allow(non_snake_case)]
+            pub fn updateSourceDropoff(&mut self, source_dropoff: u32) {
+                self.renderer.update_source_dropoff(source_dropoff);
+            }
+
+            /// Returns the number of Sources in the Spectrum.
+            #[must_use]
+            #[allow(non_snake_case, clippy::missing_const_for_fn)]
+            pub fn numSources(&self) -> u32 {
+                self.base.sources().len() as u32
+            }
+
+            /// Presses a pointer on the Spectrum, replacing any pointer with the same identifier.
+            ///
+            /// Several pointers can be pressed at once, such as one for each touch on a touchscreen.
+            ///
+            /// # Arguments
+            ///
+            /// * `id` - the identifier distinguishing the pointer from other simultaneous pointers, such as `PointerEvent.pointerId`.
+            /// * `x` - the x-coordinate of the pointer in canvas pixels.
+            /// * `y` - the y-coordinate of the pointer in canvas pixels.
+            /// * `mode` - whether the pointer grabs the nearest Source, or attracts or repels nearby Sources.
+            #[allow(non_snake_case)]
+            pub fn pointerDown(
+                &mut self,
+                id: u32,
+                x: f32,
+                y: f32,
+                mode: $crate::utils::pointer::PointerMode,
+            ) {
+                self.base.pointer_down(id, x, y, mode);
+            }
+
+            /// Moves a pressed pointer, dragging any Source it has grabbed.
+            ///
+            /// # Arguments
+            ///
+            /// * `id` - the identifier of the pointer.
+            /// * `x` - the new x-coordinate of the pointer in canvas pixels.
+            /// * `y` - the new y-coordinate of the pointer in canvas pixels.
+            #[allow(non_snake_case)]
+            pub fn pointerMove(&mut self, id: u32, x: f32, y: f32) {
+                self.base.pointer_move(id, x, y);
+            }
+
+            /// Releases a pointer, letting go of any Source it has grabbed.
+            ///
+            /// # Arguments
+            ///
+            /// * `id` - the identifier of the pointer.
+            #[allow(non_snake_case)]
+            pub fn pointerUp(&mut self, id: u32) {
+                self.base.pointer_up(id);
+            }
+
+            /// Sets how strongly attracting and repelling pointers move Sources.
+            ///
+            /// # Arguments
+            ///
+            /// * `strength` - the distance, in pixels per frame, that Sources directly beneath a pointer are moved.
+            /// * `radius` - the distance, in pixels, beyond which a pointer has no effect.
+            #[allow(non_snake_case, clippy::missing_const_for_fn)]
+            pub fn setPointerForce(&mut self, strength: f32, radius: f32) {
+                self.base.set_pointer_force(strength, radius);
+            }
+
+            /// Sets the way Sources behave when they reach the edge of the canvas.
+            ///
+            /// # Arguments
+            ///
+            /// * `boundary_mode` - whether Sources bounce, wrap around, stop, or are replaced by new Sources.
+            #[allow(non_snake_case)]
+            pub fn setBoundaryMode(&mut self, boundary_mode: $crate::utils::base::BoundaryMode) {
+                self.base.set_boundary_mode(boundary_mode);
+            }
+
+            /// Makes the Spectrum loop seamlessly, returning exactly to its current state after a number of frames, or stops it looping.
+            ///
+            /// Each source's speeds are rounded to the nearest ones which bring it back in time, so long periods stay close to the movement and color speeds.
+            ///
+            /// # Arguments
+            ///
+            /// * `loop_period` - the number of frames in the loop, or `undefined` to stop looping.
+            #[allow(non_snake_case)]
+            pub fn setLoopPeriod(&mut self, loop_period: Option<u32>) {
+                self.base.set_loop_period(loop_period);
+            }
+
+            /// Switches between pixel coordinates and normalized coordinates, which are fractions of the canvas diagonal.
+            ///
+            /// With normalized coordinates, speeds, kernel parameters and pointer forces are scaled so that the Spectrum looks the same at every resolution as it does on a 1920x1080 canvas.
+            ///
+            /// # Arguments
+            ///
+            /// * `normalized` - whether to use normalized coordinates.
+            #[allow(non_snake_case)]
+            pub fn setNormalized(&mut self, normalized: bool) {
+                self.base.set_normalized(normalized);
+            }
+
+            /// Returns the seed used to generate the Spectrum's sources.
+            ///
+            /// Passing it back into `new` with the same parameters regenerates the same scene.
+            #[must_use]
+            #[allow(clippy::missing_const_for_fn)]
+            pub fn seed(&self) -> u64 {
+                self.base.seed()
+            }
+
+            /// Increments all of the Spectrum's sources by one frame.
+            pub fn tick(&mut self) {
+                self.base.tick();
+            }
+
+            /// Increments all of the Spectrum's sources by the time elapsed since the last frame, so the animation runs at the same speed on every display.
+            ///
+            /// Elapsed times longer than one second, such as after the page was in the background, only advance the Spectrum by one second, so it does not stall while catching up.
+            ///
+            /// # Arguments
+            ///
+            /// * `dt` - the elapsed time, in seconds, which is capped at 1.
+            #[allow(non_snake_case)]
+            pub fn tickBy(&mut self, dt: f32) {
+                self.base.tick_by(dt);
+            }
+
+            /// Returns the number of frames the Spectrum has advanced by since it was created.
+            #[must_use]
+            #[allow(clippy::missing_const_for_fn)]
+            pub fn frame(&self) -> f64 {
+                self.base.frame()
+            }
+
+            /// Jumps all of the Spectrum's sources directly to a frame, forwards or backwards, without ticking through the frames in between.
+            ///
+            /// Respawning Sources are the exception, as their frames are replayed, so seeking stops an hour of frames past where the replay starts.
+            ///
+            /// # Arguments
+            ///
+            /// * `frame` - the frame to jump to, which may be fractional.
+            pub fn seek(&mut self, frame: f64) {
+                self.base.seek(frame);
+            }
+
+            /// Jumps all of the Spectrum's sources directly to a time, forwards or backwards, where one second corresponds to 60 frames.
+            ///
+            /// # Arguments
+            ///
+            /// * `seconds` - the time to jump to.
+            #[allow(non_snake_case)]
+            pub fn seekTime(&mut self, seconds: f64) {
+                self.base.seek_time(seconds);
+            }
+        }
+
+        #[cfg(feature = "gif")]
+        #[wasm_bindgen::prelude::wasm_bindgen]
+        impl $spectrum {
+            /// Encodes the Spectrum's next frames as a looping animated GIF, rendered on the CPU.
+            ///
+            /// The Spectrum itself is not advanced.
+            ///
+            /// # Arguments
+            ///
+            /// * `num_frames` - the number of frames to encode.
+            /// * `frame_delay` - the delay between frames, in hundredths of a second, which must be at least 2 as browsers slow shorter delays down to 10.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the Spectrum is too large to be encoded as a GIF.
+            #[allow(non_snake_case)]
+            pub fn toGif(
+                &self,
+                num_frames: u32,
+                frame_delay: u16,
+            ) -> Result<Vec<u8>, wasm_bindgen::JsValue> {
+                Ok($crate::export::gif::write_gif(
+                    Vec::new(),
+                    &self.base,
+                    &self.renderer,
+                    num_frames,
+                    frame_delay,
+                )
+                .map_err(|err| $crate::spectrum::error::SpectrumError::Export(err.to_string()))?)
+            }
+        }
+    };
+}
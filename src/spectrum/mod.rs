@@ -1,2 +1,10 @@
+#[macro_use]
+mod methods;
+#[macro_use]
+mod gl;
+
+pub mod error;
+pub mod scene;
 pub mod wasm;
 pub mod webgl;
+pub mod webgl2;
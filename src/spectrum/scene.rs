@@ -0,0 +1,36 @@
+use wasm_bindgen::prelude::*;
+
+use crate::spectrum::error::SpectrumError;
+use crate::utils::scene::Scene;
+
+/// Encodes a JSON scene as a compact, URL-safe scene code, without creating a Spectrum.
+///
+/// # Arguments
+///
+/// * `json` - the JSON scene, as produced by `toJSON`.
+///
+/// # Errors
+///
+/// Returns an error if the JSON is not a valid scene.
+#[wasm_bindgen(js_name = encodeScene)]
+pub fn encode_scene(json: &str) -> Result<String, JsValue> {
+    Ok(Scene::from_json(json)
+        .map_err(SpectrumError::InvalidScene)?
+        .to_code())
+}
+
+/// Decodes a scene code into a JSON scene, without creating a Spectrum.
+///
+/// # Arguments
+///
+/// * `code` - the scene code, as produced by `toCode`.
+///
+/// # Errors
+///
+/// Returns an error if the string is not a valid scene code.
+#[wasm_bindgen(js_name = decodeScene)]
+pub fn decode_scene(code: &str) -> Result<String, JsValue> {
+    Ok(Scene::from_code(code)
+        .map_err(SpectrumError::InvalidScene)?
+        .to_json())
+}
@@ -1,16 +1,18 @@
-use std::u8;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData};
 
-use crate::utils::base::SOURCE_DROPOFF_FACTOR;
-use crate::utils::base::{BaseSpectrum, Hue, RGB};
-use crate::utils::math;
+use crate::spectrum::error::SpectrumError;
+use crate::utils::base::BaseSpectrum;
+use crate::utils::color::{ColorSpace, ColorSpaceKind};
+use crate::utils::kernel::{Kernel, KernelKind};
+use crate::utils::palette::{Palette, PaletteSpace};
+use crate::utils::render::Renderer;
 
 /// A WebAssembly-only implementation of Spectrum.
 #[wasm_bindgen]
 pub struct SpectrumWasm {
-    /// The Spectrum's BaseSpectrum.
+    /// The Spectrum's `BaseSpectrum`.
     base: BaseSpectrum,
 
     /// The Spectrum's pixel data.
@@ -19,7 +21,8 @@ pub struct SpectrumWasm {
     /// The `2d` context belonging to the Spectrum's canvas.
     context: CanvasRenderingContext2d,
 
-    source_dropoff: f32,
+    /// The CPU renderer producing the Spectrum's pixel data.
+    renderer: Renderer,
 }
 
 #[wasm_bindgen]
@@ -34,7 +37,17 @@ impl SpectrumWasm {
     /// * `context` - the `2d` context belonging to the Spectrum's canvas.
     /// * `movement_speed` - the range of each Source's movement speed (`dx`, `dy`)
     /// * `color_speed` - the range of each Source's color speed (`dh`)
-    #[must_use]
+    /// * `seed` - the seed to generate the Sources from, or `undefined` to pick one at random.
+    /// * `kernel` - the kind of `Kernel` weighting each Source by its distance from a pixel, or `undefined` for inverse square.
+    /// * `kernel_parameter` - the exponent, standard deviation, scale or radius of the `Kernel`, or `undefined` to use the kind's default.
+    /// * `color_space` - the kind of color space each pixel's Hue is mapped through, or `undefined` for fully saturated HSL.
+    /// * `color_lightness` - the HSL lightness, HSV value or Oklab lightness, or `undefined` to use the kind's default.
+    /// * `color_chroma` - the HSL or HSV saturation or the Oklab chroma, or `undefined` to use the kind's default.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the canvas does not provide a `2d` context.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         width: u32,
         height: u32,
@@ -43,103 +56,262 @@ impl SpectrumWasm {
         color_speed: u32,
         source_dropoff: u32,
         canvas: &HtmlCanvasElement,
-    ) -> Self {
-        let mut spectrum = Self {
-            base: BaseSpectrum::new(width, height, num_sources, movement_speed, color_speed),
-            data: vec![0_u8; (width * height * 4) as usize],
-            context: canvas
-                .get_context("2d")
-                .unwrap()
-                .unwrap()
-                .dyn_into::<CanvasRenderingContext2d>()
-                .unwrap(),
-            source_dropoff: ((source_dropoff as f32) * SOURCE_DROPOFF_FACTOR).powi(2),
-        };
-        spectrum.draw();
+        seed: Option<u64>,
+        kernel: Option<KernelKind>,
+        kernel_parameter: Option<f32>,
+        color_space: Option<ColorSpaceKind>,
+        color_lightness: Option<f32>,
+        color_chroma: Option<f32>,
+    ) -> Result<Self, JsValue> {
+        let mut renderer = Renderer::new(source_dropoff);
+        if let Some(kernel) = kernel {
+            renderer.set_kernel(Kernel::new(kernel, kernel_parameter));
+        }
+        if let Some(color_space) = color_space {
+            renderer.set_color_space(ColorSpace::new(color_space, color_lightness, color_chroma));
+        }
 
-        spectrum
+        Ok(Self::from_parts(
+            BaseSpectrum::new(
+                width,
+                height,
+                num_sources,
+                movement_speed,
+                color_speed,
+                seed,
+            ),
+            renderer,
+            canvas,
+        )?)
     }
 
-    /// Draws to the Spectrum canvas, using the Spectrum's context to put the resulting ImageData.
+    /// Draws to the Spectrum canvas, using the Spectrum's context to put the resulting `ImageData`.
+    ///
+    /// The pixel data itself is produced by the Spectrum's `Renderer`.
     ///
-    /// Assigns Hues to each pixel based off of an average inverse square distance weighting across all Sources.
+    /// # Errors
     ///
-    /// As hue in HSL is a circular/periodic metric, a numerical average is inaccurate - instead, hue is broken into sine and cosine components which are summed and reconstructed into the resulting Hue.
-    pub fn draw(&mut self) {
+    /// Returns an error if the pixel data cannot be put onto the canvas.
+    pub fn draw(&mut self) -> Result<(), SpectrumError> {
         let width = self.base.width();
-        for y in 0..self.base.height() {
-            let y_float = y as f32;
-            let y_by_width = y * width;
-
-            for x in 0..width {
-                let mut dist_factor_inverse_sum: f32 = 0.;
-
-                let (hue_vector_cos, hue_vector_sin) =
-                    self.base
-                        .sources()
-                        .iter()
-                        .fold((0., 0.), |(sum_cos, sum_sin), source| {
-                            let x_diff = x as f32 - source.x();
-                            let y_diff = y_float - source.y();
-
-                            let dist_factor = (x_diff).mul_add(x_diff, y_diff * y_diff) + 1.;
-                            dist_factor_inverse_sum += 1. / dist_factor;
-
-                            (
-                                sum_cos + source.hue_cos() / dist_factor,
-                                sum_sin + source.hue_sin() / dist_factor,
-                            )
-                        });
-
-                dist_factor_inverse_sum = dist_factor_inverse_sum.min(1.);
-                let adjusted_dist_factor_inverse_sum =
-                    dist_factor_inverse_sum.powf(self.source_dropoff);
-                let alpha = (f32::from(u8::MAX) * adjusted_dist_factor_inverse_sum) as u8;
-
-                let RGB(r, g, b) =
-                    Hue::new(math::atan2_approx(hue_vector_cos, hue_vector_sin)).to_rgb();
-
-                let start = ((x + y_by_width) * 4) as usize;
-
-                unsafe {
-                    *self.data.get_unchecked_mut(start) = r;
-                    *self.data.get_unchecked_mut(start + 1) = g;
-                    *self.data.get_unchecked_mut(start + 2) = b;
-                    *self.data.get_unchecked_mut(start + 3) = alpha;
-                }
-            }
-        }
+        self.renderer.draw(&self.base, &mut self.data);
+
+        let image_data = ImageData::new_with_u8_clamped_array(
+            wasm_bindgen::Clamped(self.data.as_mut_slice()),
+            width,
+        )
+        .map_err(|err| SpectrumError::draw(&err))?;
 
         self.context
-            .put_image_data(
-                &ImageData::new_with_u8_clamped_array(
-                    wasm_bindgen::Clamped(self.data.as_mut_slice()),
-                    width,
-                )
-                .unwrap(),
-                0_f64,
-                0_f64,
-            )
-            .unwrap();
+            .put_image_data(&image_data, 0_f64, 0_f64)
+            .map_err(|err| SpectrumError::draw(&err))
     }
 
+    /// Switches the `Kernel` weighting each Source by its distance from a pixel.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - the kind of `Kernel`.
+    /// * `parameter` - the exponent, standard deviation, scale or radius of the `Kernel`, or `undefined` to use the kind's default.
     #[allow(non_snake_case)]
-    pub fn updateMovementSpeed(&mut self, movement_speed: u32) {
-        self.base.update_movement_speed(movement_speed);
+    pub fn setKernel(&mut self, kind: KernelKind, parameter: Option<f32>) {
+        self.renderer.set_kernel(Kernel::new(kind, parameter));
     }
 
+    /// Switches the color space each pixel's Hue is mapped through.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - the kind of color space.
+    /// * `lightness` - the HSL lightness, HSV value or Oklab lightness, or `undefined` to use the kind's default.
+    /// * `chroma` - the HSL or HSV saturation or the Oklab chroma, or `undefined` to use the kind's default.
     #[allow(non_snake_case)]
-    pub fn updateColorSpeed(&mut self, color_speed: u32) {
-        self.base.update_color_speed(color_speed);
+    pub fn setColorSpace(
+        &mut self,
+        kind: ColorSpaceKind,
+        lightness: Option<f32>,
+        chroma: Option<f32>,
+    ) {
+        self.renderer
+            .set_color_space(ColorSpace::new(kind, lightness, chroma));
+    }
+
+    /// Maps each pixel's Hue through a cyclic gradient instead of the color space.
+    ///
+    /// # Arguments
+    ///
+    /// * `colors` - the color of each of the gradient's stops, as `0xRRGGBB`.
+    /// * `positions` - the position of each stop in [0, 1), or `undefined` to space the stops evenly.
+    /// * `space` - the color space stops are interpolated in, or `undefined` for Oklab.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are no colors, or the positions do not match the colors.
+    #[allow(non_snake_case, clippy::needless_pass_by_value)]
+    pub fn setPalette(
+        &mut self,
+        colors: &[u32],
+        positions: Option<Vec<f32>>,
+        space: Option<PaletteSpace>,
+    ) -> Result<(), JsValue> {
+        let palette = Palette::new(
+            colors,
+            positions.as_deref(),
+            space.unwrap_or(PaletteSpace::Oklab),
+        )
+        .map_err(SpectrumError::InvalidPalette)?;
+        self.renderer.set_palette(Some(palette));
+
+        Ok(())
+    }
+
+    /// Stops mapping each pixel's Hue through a gradient, returning to the color space.
+    #[allow(non_snake_case, clippy::missing_const_for_fn)]
+    pub fn clearPalette(&mut self) {
+        self.renderer.set_palette(None);
     }
 
+    /// Sets whether distant groups of Sources are approximated as single Sources, which keeps Spectrums with thousands of Sources interactive.
+    ///
+    /// # Arguments
+    ///
+    /// * `far_field_tolerance` - the largest ratio of a group's size to its distance from a pixel at which it is approximated, such as 0.5, or `undefined` to draw every Source exactly.
     #[allow(non_snake_case)]
-    pub fn updateSourceDropoff(&mut self, source_dropoff: u32) {
-        self.source_dropoff = ((source_dropoff as f32) * SOURCE_DROPOFF_FACTOR).powi(2);
+    pub fn setFarFieldTolerance(&mut self, far_field_tolerance: Option<f32>) {
+        self.renderer.set_far_field_tolerance(far_field_tolerance);
+    }
+
+    /// Sets the fraction of the canvas resolution the Spectrum is evaluated at before being upsampled to the pixel data.
+    ///
+    /// As the Spectrum is smooth, a scale of 0.5 is nearly indistinguishable from full resolution while evaluating a quarter as many pixels, without shrinking the canvas.
+    ///
+    /// # Arguments
+    ///
+    /// * `render_scale` - the fraction of the resolution, clamped to [0.25, 1].
+    #[allow(non_snake_case, clippy::missing_const_for_fn)]
+    pub fn setRenderScale(&mut self, render_scale: f32) {
+        self.renderer.set_render_scale(render_scale);
     }
 
-    /// Increments all of the Spectrum's sources by one frame.
-    pub fn tick(&mut self) {
-        self.base.tick();
+    /// Adds a randomly generated Source, keeping the existing Sources.
+    #[allow(non_snake_case)]
+    pub fn addSource(&mut self) {
+        self.base.add_source();
+    }
+
+    /// Removes a Source, keeping the other Sources.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - the index of the Source to remove.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no Source at `index`.
+    #[allow(non_snake_case)]
+    pub fn removeSource(&mut self, index: u32) -> Result<(), JsValue> {
+        self.base
+            .remove_source(index as usize)
+            .ok_or(SpectrumError::NoSuchSource(index))?;
+
+        Ok(())
+    }
+
+    /// Replaces the state of a Source.
+    ///
+    /// Positions and speeds are in pixels, or fractions of the canvas diagonal if coordinates are normalized.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - the index of the Source to replace.
+    /// * `x` - the x-coordinate of the Source, which is clamped to the canvas.
+    /// * `y` - the y-coordinate of the Source, which is clamped to the canvas.
+    /// * `hue` - the Hue value of the Source, in radians.
+    /// * `dx` - the rate of movement in the x direction.
+    /// * `dy` - the rate of movement in the y direction.
+    /// * `dh` - the rate of change in the Source's Hue.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no Source at `index`.
+    #[allow(non_snake_case, clippy::too_many_arguments)]
+    pub fn setSource(
+        &mut self,
+        index: u32,
+        x: f32,
+        y: f32,
+        hue: f32,
+        dx: f32,
+        dy: f32,
+        dh: f32,
+    ) -> Result<(), JsValue> {
+        let source = self.base.create_source(x, y, hue, dx, dy, dh);
+        self.base
+            .set_source(index as usize, source)
+            .ok_or(SpectrumError::NoSuchSource(index))?;
+
+        Ok(())
+    }
+
+    /// Resizes the Spectrum and its canvas, keeping the same artwork by moving each source to the same relative position.
+    ///
+    /// The pixel data is reallocated to the new size, and since resizing the canvas clears it, the Spectrum is redrawn immediately.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - the new width of the Spectrum.
+    /// * `height` - the new height of the Spectrum.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the resized Spectrum cannot be drawn.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), SpectrumError> {
+        self.base.resize(width, height);
+        self.data.resize(width as usize * height as usize * 4, 0);
+
+        if let Some(canvas) = self.context.canvas() {
+            canvas.set_width(width);
+            canvas.set_height(height);
+        }
+
+        self.draw()
     }
 }
+
+impl SpectrumWasm {
+    /// Creates a new Spectrum from an existing `BaseSpectrum` and `Renderer`, drawing its first frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - the Spectrum's `BaseSpectrum`.
+    /// * `renderer` - the Spectrum's `Renderer`.
+    /// * `canvas` - the canvas to draw the Spectrum to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the canvas does not provide a `2d` context, or the first frame cannot be drawn.
+    fn from_parts(
+        base: BaseSpectrum,
+        renderer: Renderer,
+        canvas: &HtmlCanvasElement,
+    ) -> Result<Self, SpectrumError> {
+        let context = canvas
+            .get_context("2d")
+            .ok()
+            .flatten()
+            .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok())
+            .ok_or(SpectrumError::NoContext("2d"))?;
+
+        let mut spectrum = Self {
+            data: vec![0_u8; base.width() as usize * base.height() as usize * 4],
+            base,
+            context,
+            renderer,
+        };
+        spectrum.draw()?;
+
+        Ok(spectrum)
+    }
+}
+
+spectrum_methods!(SpectrumWasm);
@@ -1,32 +1,61 @@
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
-use web_sys::{HtmlCanvasElement, WebGlProgram, WebGlRenderingContext, WebGlShader};
+use web_sys::{
+    HtmlCanvasElement, WebGlFramebuffer, WebGlProgram, WebGlRenderingContext, WebGlTexture,
+};
 
+use crate::spectrum::error::SpectrumError;
+use crate::spectrum::gl::{get_shader_source, Glsl, OTHER_UNIFORM_VECTORS};
 use crate::utils::base::BaseSpectrum;
-use crate::utils::base::SOURCE_DROPOFF_FACTOR;
+use crate::utils::color::{ColorSpace, ColorSpaceKind};
+use crate::utils::kernel::{Kernel, KernelKind};
+use crate::utils::render::Renderer;
+
+/// The maximum number of Sources in each row of the source texture.
+const SOURCE_TEXTURE_WIDTH: u32 = 1024;
 
 /// A WebGL + WebAssembly implementation of Spectrum.
+///
+/// Sources are passed to the fragment shader in a float texture if the context supports `OES_texture_float`, and in a uniform array otherwise, which limits their number to the context's `MAX_FRAGMENT_UNIFORM_VECTORS`.
+///
+/// Below a render scale of 1, the Spectrum is drawn into an offscreen framebuffer at a fraction of the canvas resolution, which is then upsampled to the canvas.
 #[wasm_bindgen]
 pub struct SpectrumWebGL {
     base: BaseSpectrum,
     context: WebGlRenderingContext,
     program: WebGlProgram,
-    source_dropoff: f32,
+    renderer: Renderer,
+    palette_texture: WebGlTexture,
+    source_texture: Option<WebGlTexture>,
+    program_sources: u32,
+    framebuffer: WebGlFramebuffer,
+    frame_texture: WebGlTexture,
+    upsample_program: WebGlProgram,
 }
 
 #[wasm_bindgen]
 impl SpectrumWebGL {
-    /// Creates a new SpectrumWebGL.
+    /// Creates a new `SpectrumWebGL`.
     ///
     /// # Arguments
     ///
-    /// * `width` - the SpectrumWebGL's width.
-    /// * `height` - the SpectrumWebGL's height.
-    /// * `num_sources` - the number of Sources in the SpectrumWebGL.
-    /// * `context` - the `webgl` context belonging to the SpectrumWebGL's canvas.
+    /// * `width` - the `SpectrumWebGL`'s width.
+    /// * `height` - the `SpectrumWebGL`'s height.
+    /// * `num_sources` - the number of Sources in the `SpectrumWebGL`.
+    /// * `context` - the `webgl` context belonging to the `SpectrumWebGL`'s canvas.
     /// * `movement_speed` - the range of each Source's movement speed (`dx`, `dy`)
     /// * `color_speed` - the range of each Source's color speed (`dh`)
-    #[must_use]
+    /// * `seed` - the seed to generate the Sources from, or `undefined` to pick one at random.
+    /// * `kernel` - the kind of `Kernel` weighting each Source by its distance from a pixel, or `undefined` for inverse square.
+    /// * `kernel_parameter` - the exponent, standard deviation, scale or radius of the `Kernel`, or `undefined` to use the kind's default.
+    /// * `color_space` - the kind of color space each pixel's Hue is mapped through, or `undefined` for fully saturated HSL.
+    /// * `color_lightness` - the HSL lightness, HSV value or Oklab lightness, or `undefined` to use the kind's default.
+    /// * `color_chroma` - the HSL or HSV saturation or the Oklab chroma, or `undefined` to use the kind's default.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the canvas does not provide a `webgl` context or the shaders cannot be set up.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         width: u32,
         height: u32,
@@ -35,49 +64,240 @@ impl SpectrumWebGL {
         color_speed: u32,
         source_dropoff: u32,
         canvas: &HtmlCanvasElement,
-    ) -> Self {
-        let context = canvas
-            .get_context("webgl")
-            .unwrap()
-            .unwrap()
-            .dyn_into::<WebGlRenderingContext>()
-            .unwrap();
+        seed: Option<u64>,
+        kernel: Option<KernelKind>,
+        kernel_parameter: Option<f32>,
+        color_space: Option<ColorSpaceKind>,
+        color_lightness: Option<f32>,
+        color_chroma: Option<f32>,
+    ) -> Result<Self, JsValue> {
+        let mut renderer = Renderer::new(source_dropoff);
+        if let Some(kernel) = kernel {
+            renderer.set_kernel(Kernel::new(kernel, kernel_parameter));
+        }
+        if let Some(color_space) = color_space {
+            renderer.set_color_space(ColorSpace::new(color_space, color_lightness, color_chroma));
+        }
 
-        let vertex_shader = compile_shader(
-            &context,
-            WebGlRenderingContext::VERTEX_SHADER,
-            r#"
-                attribute vec4 a_position;
-
-                void main(void) {
-                    gl_Position = a_position;
-                }
-            "#,
-        );
+        Ok(Self::from_parts(
+            BaseSpectrum::new(
+                width,
+                height,
+                num_sources,
+                movement_speed,
+                color_speed,
+                seed,
+            ),
+            renderer,
+            canvas,
+        )?)
+    }
 
-        let fragment_shader = compile_shader(
-            &context,
-            WebGlRenderingContext::FRAGMENT_SHADER,
-            get_shader_source(num_sources).as_str(),
+    /// Draws to the Spectrum canvas, adjusting the context's shaders to match the current state.
+    ///
+    /// Assigns Hues to each pixel based off of an average distance weighting across all Sources, using the current `Kernel`.
+    ///
+    /// As hue is a circular/periodic metric, a numerical average is inaccurate - instead, hue is broken into sine and cosine components which are summed and reconstructed into the resulting Hue.
+    ///
+    /// Below a render scale of 1, the Spectrum is drawn into the frame texture and then upsampled to the canvas.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the browser rejects the Sources uploaded to the source texture.
+    pub fn draw(&self) -> Result<(), SpectrumError> {
+        let render_scale = self.renderer.render_scale();
+        let unit_size = self.base.unit_size();
+        let mut source_info: Vec<f32> = self
+            .base
+            .sources()
+            .iter()
+            .flat_map(|source| {
+                vec![
+                    source.x() * unit_size,
+                    source.y() * unit_size,
+                    source.hue_cos(),
+                    source.hue_sin(),
+                ]
+            })
+            .collect();
+
+        let context = &self.context;
+        context.use_program(Some(&self.program));
+
+        let rows = if render_scale < 1. {
+            let (width, height) = self.frame_size();
+            context.bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, Some(&self.framebuffer));
+            context.viewport(0, 0, width.cast_signed(), height.cast_signed());
+            height
+        } else {
+            self.base.height()
+        };
+
+        let source_info_loc = context.get_uniform_location(&self.program, "sources");
+
+        let num_sources_loc = context.get_uniform_location(&self.program, "num_sources");
+
+        let source_dropoff_info_loc = context.get_uniform_location(&self.program, "source_dropoff");
+
+        let height_loc = context.get_uniform_location(&self.program, "height");
+
+        let pixel_size_loc = context.get_uniform_location(&self.program, "pixel_size");
+
+        let kernel_parameter_loc = context.get_uniform_location(&self.program, "kernel_parameter");
+
+        let color_lightness_loc = context.get_uniform_location(&self.program, "color_lightness");
+
+        let color_chroma_loc = context.get_uniform_location(&self.program, "color_chroma");
+
+        if let Some(source_texture) = &self.source_texture {
+            let (width, height) = source_texture_size(self.program_sources);
+            source_info.resize((width * height * 4) as usize, 0.);
+            self.upload_sources(source_texture, width, height, &source_info)?;
+            context.uniform1i(
+                num_sources_loc.as_ref(),
+                (self.base.sources().len() as u32).cast_signed(),
+            );
+        } else if !source_info.is_empty() {
+            context.uniform1fv_with_f32_array(source_info_loc.as_ref(), source_info.as_slice());
+        }
+        context.uniform1f(
+            source_dropoff_info_loc.as_ref(),
+            self.renderer.source_dropoff_exponent(),
+        );
+        context.uniform1f(height_loc.as_ref(), rows as f32);
+        context.uniform1f(
+            pixel_size_loc.as_ref(),
+            self.base.pixel_size() / render_scale,
+        );
+        context.uniform1f(
+            kernel_parameter_loc.as_ref(),
+            self.renderer.kernel().parameter(),
+        );
+        context.uniform1f(
+            color_lightness_loc.as_ref(),
+            self.renderer.color_space().lightness(),
+        );
+        context.uniform1f(
+            color_chroma_loc.as_ref(),
+            self.renderer.color_space().chroma(),
         );
 
-        let program = context.create_program().unwrap();
+        context.draw_arrays(WebGlRenderingContext::TRIANGLE_FAN, 0, 4);
 
-        context.attach_shader(&program, &vertex_shader);
-        context.attach_shader(&program, &fragment_shader);
-        context.link_program(&program);
-        context
-            .get_program_parameter(&program, WebGlRenderingContext::LINK_STATUS)
-            .as_bool()
-            .unwrap();
+        if render_scale < 1. {
+            self.draw_upsampled();
+        }
 
-        context.use_program(Some(&program));
+        Ok(())
+    }
 
-        let position_attribute_loc = context.get_attrib_location(&program, "a_position") as u32;
+    /// Returns whether Sources are passed to the shader in a float texture, in which case their number is not limited by the context's `MAX_FRAGMENT_UNIFORM_VECTORS`.
+    #[must_use]
+    #[allow(non_snake_case, clippy::missing_const_for_fn)]
+    pub fn usesSourceTexture(&self) -> bool {
+        self.source_texture.is_some()
+    }
+
+    /// Adds a randomly generated Source, keeping the existing Sources.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, without adding the Source, if the device cannot pass another Source to the shader or the shader program cannot be rebuilt.
+    #[allow(non_snake_case)]
+    pub fn addSource(&mut self) -> Result<(), JsValue> {
+        self.base.add_source();
+
+        if let Err(err) = self.fit_program() {
+            self.base.remove_source(self.base.sources().len() - 1);
+            return Err(err.into());
+        }
+
+        Ok(())
+    }
+
+    /// Removes a Source, keeping the other Sources.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - the index of the Source to remove.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no Source at `index`, or the shader program cannot be rebuilt.
+    #[allow(non_snake_case)]
+    pub fn removeSource(&mut self, index: u32) -> Result<(), JsValue> {
+        self.base
+            .remove_source(index as usize)
+            .ok_or(SpectrumError::NoSuchSource(index))?;
+        self.fit_program()?;
+
+        Ok(())
+    }
+
+    /// Replaces the state of a Source.
+    ///
+    /// Positions and speeds are in pixels, or fractions of the canvas diagonal if coordinates are normalized.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - the index of the Source to replace.
+    /// * `x` - the x-coordinate of the Source, which is clamped to the canvas.
+    /// * `y` - the y-coordinate of the Source, which is clamped to the canvas.
+    /// * `hue` - the Hue value of the Source, in radians.
+    /// * `dx` - the rate of movement in the x direction.
+    /// * `dy` - the rate of movement in the y direction.
+    /// * `dh` - the rate of change in the Source's Hue.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no Source at `index`.
+    #[allow(non_snake_case, clippy::too_many_arguments)]
+    pub fn setSource(
+        &mut self,
+        index: u32,
+        x: f32,
+        y: f32,
+        hue: f32,
+        dx: f32,
+        dy: f32,
+        dh: f32,
+    ) -> Result<(), JsValue> {
+        let source = self.base.create_source(x, y, hue, dx, dy, dh);
+        self.base
+            .set_source(index as usize, source)
+            .ok_or(SpectrumError::NoSuchSource(index))?;
+
+        Ok(())
+    }
+}
+
+impl SpectrumWebGL {
+    /// Creates a new `SpectrumWebGL` from an existing `BaseSpectrum` and `Renderer`, drawing its first frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - the `SpectrumWebGL`'s `BaseSpectrum`.
+    /// * `renderer` - the `Renderer` holding the `SpectrumWebGL`'s drawing settings.
+    /// * `canvas` - the canvas to draw the `SpectrumWebGL` to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the canvas does not provide a `webgl` context or the shaders cannot be set up.
+    fn from_parts(
+        base: BaseSpectrum,
+        renderer: Renderer,
+        canvas: &HtmlCanvasElement,
+    ) -> Result<Self, SpectrumError> {
+        let context = canvas
+            .get_context("webgl")
+            .ok()
+            .flatten()
+            .and_then(|context| context.dyn_into::<WebGlRenderingContext>().ok())
+            .ok_or(SpectrumError::NoContext("webgl"))?;
 
         let vertex_coords = [-1_f32, -1_f32, 1_f32, -1_f32, 1_f32, 1_f32, -1_f32, 1_f32];
 
-        let buffer = context.create_buffer().unwrap();
+        let buffer = context.create_buffer().ok_or(SpectrumError::ContextLost)?;
         context.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&buffer));
 
         unsafe {
@@ -90,178 +310,302 @@ impl SpectrumWebGL {
             );
         }
 
-        context.enable_vertex_attrib_array(position_attribute_loc);
-
-        context.vertex_attrib_pointer_with_i32(
-            position_attribute_loc,
+        // Without the extension, Sources fall back to a uniform array.
+        let source_texture = context
+            .get_extension("OES_texture_float")
+            .ok()
+            .flatten()
+            .map(|_| {
+                // Float textures cannot be filtered without another extension.
+                let source_texture = create_texture(
+                    &context,
+                    WebGlRenderingContext::TEXTURE1,
+                    WebGlRenderingContext::NEAREST,
+                    WebGlRenderingContext::CLAMP_TO_EDGE,
+                );
+
+                context.active_texture(WebGlRenderingContext::TEXTURE0);
+                source_texture
+            })
+            .transpose()?;
+
+        // Each tap of the bicubic filter reads a single texel.
+        let frame_texture = create_texture(
+            &context,
+            WebGlRenderingContext::TEXTURE2,
+            WebGlRenderingContext::NEAREST,
+            WebGlRenderingContext::CLAMP_TO_EDGE,
+        )?;
+        context.active_texture(WebGlRenderingContext::TEXTURE0);
+        let framebuffer = create_framebuffer(&context, &frame_texture)?;
+
+        let upsample_program = link_program(&context, Glsl::Es1.upsample_shader())?;
+        context.uniform1i(
+            context
+                .get_uniform_location(&upsample_program, "frame")
+                .as_ref(),
             2,
-            WebGlRenderingContext::FLOAT,
-            false,
-            0,
-            0,
         );
 
+        let program_sources =
+            get_program_sources(base.sources().len() as u32, source_texture.is_some());
+        let program = create_program(
+            &context,
+            program_sources,
+            source_texture.is_some(),
+            &renderer,
+        )?;
+
+        let palette_texture = create_texture(
+            &context,
+            WebGlRenderingContext::TEXTURE0,
+            WebGlRenderingContext::LINEAR,
+            WebGlRenderingContext::REPEAT,
+        )?;
+
         let spectrum = Self {
-            base: BaseSpectrum::new(width, height, num_sources, movement_speed, color_speed),
+            base,
             context,
             program,
-            source_dropoff: ((source_dropoff as f32) * SOURCE_DROPOFF_FACTOR).powi(2),
+            renderer,
+            palette_texture,
+            source_texture,
+            program_sources,
+            framebuffer,
+            frame_texture,
+            upsample_program,
         };
 
-        spectrum.draw();
+        spectrum.upload_palette()?;
+        spectrum.allocate_frame_texture()?;
 
-        spectrum
+        spectrum.draw()?;
+
+        Ok(spectrum)
     }
 
-    /// Draws to the Spectrum canvas, adjusting the context's shaders to match the current state.
+    /// Uploads Source data to the source texture, one RGBA texel per Source.
     ///
-    /// Assigns Hues to each pixel based off of an average inverse square distance weighting across all Sources.
+    /// # Arguments
     ///
-    /// As hue in HSL is a circular/periodic metric, a numerical average is inaccurate - instead, hue is broken into sine and cosine components which are summed and reconstructed into the resulting Hue.
-    pub fn draw(&self) {
-        let source_info: Vec<f32> = self
-            .base
-            .sources()
-            .iter()
-            .flat_map(|source| vec![source.x(), source.y(), source.hue_cos(), source.hue_sin()])
-            .collect();
-
+    /// * `source_texture` - the source texture.
+    /// * `width` - the width of the source texture.
+    /// * `height` - the height of the source texture.
+    /// * `source_info` - the position and Hue of each Source, padded to fill the texture.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the browser rejects the upload.
+    fn upload_sources(
+        &self,
+        source_texture: &WebGlTexture,
+        width: u32,
+        height: u32,
+        source_info: &[f32],
+    ) -> Result<(), SpectrumError> {
         let context = &self.context;
 
-        let source_info_loc = context.get_uniform_location(&self.program, "sources");
+        context.active_texture(WebGlRenderingContext::TEXTURE1);
+        context.bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(source_texture));
 
-        let source_dropoff_info_loc = context.get_uniform_location(&self.program, "source_dropoff");
+        unsafe {
+            let source_array = js_sys::Float32Array::view(source_info);
+
+            context
+                .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_array_buffer_view(
+                    WebGlRenderingContext::TEXTURE_2D,
+                    0,
+                    WebGlRenderingContext::RGBA.cast_signed(),
+                    width.cast_signed(),
+                    height.cast_signed(),
+                    0,
+                    WebGlRenderingContext::RGBA,
+                    WebGlRenderingContext::FLOAT,
+                    Some(&source_array),
+                )
+                .map_err(|err| SpectrumError::draw(&err))?;
+        }
 
-        context.uniform1fv_with_f32_array(source_info_loc.as_ref(), source_info.as_slice());
-        context.uniform1f(source_dropoff_info_loc.as_ref(), self.source_dropoff);
+        context.active_texture(WebGlRenderingContext::TEXTURE0);
 
-        context.draw_arrays(WebGlRenderingContext::TRIANGLE_FAN, 0, 4);
+        Ok(())
     }
 
-    #[allow(non_snake_case)]
-    pub fn updateMovementSpeed(&mut self, movement_speed: u32) {
-        self.base.update_movement_speed(movement_speed);
-    }
+    /// Rebuilds the shader program if it was built for a different number of Sources.
+    ///
+    /// With a source texture, the program iterates over a power-of-two capacity, so it only needs rebuilding when the Sources outgrow it or shrink to half of it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, keeping the current shader program, if the new one cannot be set up.
+    fn fit_program(&mut self) -> Result<(), SpectrumError> {
+        let program_sources = get_program_sources(
+            self.base.sources().len() as u32,
+            self.source_texture.is_some(),
+        );
 
-    #[allow(non_snake_case)]
-    pub fn updateColorSpeed(&mut self, color_speed: u32) {
-        self.base.update_color_speed(color_speed);
-    }
+        if program_sources != self.program_sources {
+            self.rebuild_program()?;
+        }
 
-    #[allow(non_snake_case)]
-    pub fn updateSourceDropoff(&mut self, source_dropoff: u32) {
-        self.source_dropoff = ((source_dropoff as f32) * SOURCE_DROPOFF_FACTOR).powi(2);
+        Ok(())
     }
 
-    /// Increments all of the Spectrum's sources by one frame.
-    pub fn tick(&mut self) {
-        self.base.tick();
+    /// Replaces the shader program with one matching the current number of Sources and the `Renderer`'s settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, keeping the current shader program, if the new one cannot be set up.
+    fn rebuild_program(&mut self) -> Result<(), SpectrumError> {
+        let program_sources = get_program_sources(
+            self.base.sources().len() as u32,
+            self.source_texture.is_some(),
+        );
+        let program = create_program(
+            &self.context,
+            program_sources,
+            self.source_texture.is_some(),
+            &self.renderer,
+        )?;
+
+        self.context.delete_program(Some(&self.program));
+        self.program = program;
+        self.program_sources = program_sources;
+
+        Ok(())
     }
 }
 
-/// Compiles a `WebGL` shader from source.
-///
-/// Obtained from the [`wasm-bindgen` Guide `WebGL` example](https://rustwasm.github.io/wasm-bindgen/examples/webgl.html).
+spectrum_methods!(SpectrumWebGL);
+gl_spectrum_methods!(
+    SpectrumWebGL,
+    WebGlRenderingContext,
+    WebGlRenderingContext::RGBA
+);
+
+gl_helpers!(WebGlRenderingContext, Glsl::Es1);
+
+/// Compiles and links the Spectrum's shader program and makes it the context's current program.
 ///
 /// # Parameters
 ///
 /// * `context` - the `WebGL` context.
-/// * `shader_type` - the shader's type - vertex or fragment shader.
-/// * `source` - the GLSL shader source.
-fn compile_shader(context: &WebGlRenderingContext, shader_type: u32, source: &str) -> WebGlShader {
-    let shader = context.create_shader(shader_type).unwrap();
-    context.shader_source(&shader, source);
-    context.compile_shader(&shader);
+/// * `num_sources` - the number of Sources the fragment shader iterates over.
+/// * `source_texture` - whether the fragment shader reads Sources from the source texture rather than a uniform array.
+/// * `renderer` - the `Renderer` whose kinds of `Kernel` and `ColorSpace`, and whether it has a `Palette`, the fragment shader uses.
+///
+/// # Errors
+///
+/// Returns an error, leaving the context's current program in place, if the device cannot pass the Sources to the shader as uniforms, or a shader fails to compile or the program fails to link.
+fn create_program(
+    context: &WebGlRenderingContext,
+    num_sources: u32,
+    source_texture: bool,
+    renderer: &Renderer,
+) -> Result<WebGlProgram, SpectrumError> {
+    if !source_texture {
+        let max = get_max_uniform_sources(context);
+
+        if num_sources > max {
+            return Err(SpectrumError::TooManySources {
+                sources: num_sources,
+                max,
+            });
+        }
+    }
+
+    let (source_declaration, source_loop) = get_source_glsl(num_sources, source_texture);
+    let program = link_program(
+        context,
+        get_shader_source(Glsl::Es1, renderer, &source_declaration, &source_loop).as_str(),
+    )?;
 
+    if source_texture {
+        context.uniform1i(
+            context.get_uniform_location(&program, "sources").as_ref(),
+            1,
+        );
+    }
+
+    Ok(program)
+}
+
+/// Returns the largest number of Sources the device can pass to the fragment shader as uniforms.
+///
+/// Each Source takes four uniform vectors, after the `OTHER_UNIFORM_VECTORS` taken by the shader's other uniforms.
+fn get_max_uniform_sources(context: &WebGlRenderingContext) -> u32 {
     context
-        .get_shader_parameter(&shader, WebGlRenderingContext::COMPILE_STATUS)
-        .as_bool()
-        .unwrap();
+        .get_parameter(WebGlRenderingContext::MAX_FRAGMENT_UNIFORM_VECTORS)
+        .ok()
+        .and_then(|max_vectors| max_vectors.as_f64())
+        .map_or(u32::MAX, |max_vectors| {
+            (max_vectors as u32).saturating_sub(OTHER_UNIFORM_VECTORS) / 4
+        })
+}
+
+/// Returns the number of Sources a shader program should iterate over.
+///
+/// # Arguments
+///
+/// * `num_sources` - the number of Sources in the Spectrum.
+/// * `source_texture` - whether Sources are read from the source texture, whose capacity is rounded up to a power of two so that adding or removing a Source rarely rebuilds the program.
+fn get_program_sources(num_sources: u32, source_texture: bool) -> u32 {
+    if source_texture {
+        num_sources.max(1).next_power_of_two()
+    } else {
+        num_sources
+    }
+}
 
-    shader
+/// Returns the width and height of a source texture holding a number of Sources, which must be a power of two.
+fn source_texture_size(num_sources: u32) -> (u32, u32) {
+    let width = num_sources.min(SOURCE_TEXTURE_WIDTH);
+
+    (width, num_sources / width)
 }
 
-fn get_shader_source(num_sources: u32) -> String {
-    format!(
-        r#"
-            #define PI 3.141592653589793
-            #define TWO_PI 6.283185307179586
-            #define PI_4 0.7853981633974483
-            #define PI_3 1.0471975511965976
-            #define PI_2 1.5707963267948966
-            #define PI_3_2 4.71238898038469
-            #define PI_2_3 2.0943951023931953
-            #define PI_4_3 4.1887902047863905
-            #define PI_5_3 5.235987755982989
-
-            precision highp float;
-
-            uniform float sources[{}];
-            uniform float source_dropoff;
-
-            float atan_approx(float quotient) {{
-                return (PI_4 + 0.273 * (1.0 - abs(quotient))) * quotient;
-            }}
-
-            float atan2_approx(float x, float y) {{
-                if (abs(x) > abs(y)) {{
-                    if (x < 0.0) {{
-                        return atan_approx(y / x) + PI;
-                    }} else if (y < 0.0) {{
-                        return atan_approx(y / x) + TWO_PI;
-                    }} else {{
-                        return atan_approx(y / x);
-                    }}
-                }} else if (y < 0.0) {{
-                    return PI_3_2 - atan_approx(x / y);
-                }} else {{
-                    return PI_2 - atan_approx(x / y);
-                }}
-            }}
-
-            void main() {{
-                float x = gl_FragCoord[0];
-                float y = gl_FragCoord[1];
-                float cos_sum = 0.0;
-                float sin_sum = 0.0;
-                float dist_factor_inverse_sum = 0.0;
-
-                for (int i = 0; i < {}; i++) {{
-                    float dist_factor = pow(sources[4 * i] - x, 2.0) + pow(sources[4 * i + 1] - y, 2.0) + 1.0;
-                    cos_sum += sources[4 * i + 2] / dist_factor;
-                    sin_sum += sources[4 * i + 3] / dist_factor;
-
-                    dist_factor_inverse_sum += 1.0 / dist_factor;
-                }}
-
-                if (dist_factor_inverse_sum > 1.0) {{
-                    dist_factor_inverse_sum = 1.0;
-                }}
-
-                float alpha_factor = pow(dist_factor_inverse_sum, source_dropoff);
-
-                float hue = atan2_approx(sin_sum, cos_sum);
-                
-                float secondary = (1.0 - abs(mod((hue / PI_3), 2.0) - 1.0)) * alpha_factor;
-
-                if (hue < PI) {{
-                    if (hue < PI_3) {{
-                        gl_FragColor = vec4(alpha_factor, secondary, 0.0, 1.0);
-                    }} else if (hue < PI_2_3) {{
-                        gl_FragColor = vec4(secondary, alpha_factor, 0.0, 1.0);
-                    }} else {{
-                        gl_FragColor = vec4(0.0, alpha_factor, secondary, 1.0);
+/// Returns the GLSL declaring the fragment shader's Sources, and the head of the loop reading each one into `vec4 source`.
+///
+/// # Arguments
+///
+/// * `num_sources` - the number of Sources the fragment shader iterates over.
+/// * `source_texture` - whether Sources are read from the source texture rather than a uniform array.
+fn get_source_glsl(num_sources: u32, source_texture: bool) -> (String, String) {
+    if source_texture {
+        let (width, height) = source_texture_size(num_sources);
+
+        (
+            String::from(
+                r"
+            uniform highp sampler2D sources;
+            uniform int num_sources;",
+            ),
+            // GLSL ES 1.00 loops need a constant bound, so the loop runs over the texture's capacity and stops at the last Source.
+            format!(
+                r"
+                for (int i = 0; i < {num_sources}; i++) {{
+                    if (i >= num_sources) {{
+                        break;
                     }}
-                }} else if (hue < PI_4_3) {{
-                    gl_FragColor = vec4(0.0, secondary, alpha_factor, 1.0);
-                }} else if (hue < PI_5_3) {{
-                    gl_FragColor = vec4(secondary, 0.0, alpha_factor, 1.0);
-                }} else {{
-                    gl_FragColor = vec4(alpha_factor, 0.0, secondary, 1.0);
-                }}
-            }}
-        "#,
-        num_sources * 4,
-        num_sources,
-    )
+
+                    float row = floor(float(i) / {width}.0);
+                    vec4 source = texture2D(sources, (vec2(float(i) - row * {width}.0, row) + 0.5) / vec2({width}.0, {height}.0));"
+            ),
+        )
+    } else {
+        // GLSL does not allow empty arrays, so an unused Source is declared when there are none.
+        let sources_length = num_sources.max(1) * 4;
+
+        (
+            format!(
+                r"
+            uniform float sources[{sources_length}];"
+            ),
+            format!(
+                r"
+                for (int i = 0; i < {num_sources}; i++) {{
+                    vec4 source = vec4(sources[4 * i], sources[4 * i + 1], sources[4 * i + 2], sources[4 * i + 3]);"
+            ),
+        )
+    }
 }
@@ -0,0 +1,486 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    HtmlCanvasElement, WebGl2RenderingContext, WebGlFramebuffer, WebGlProgram, WebGlTexture,
+};
+
+use crate::spectrum::error::SpectrumError;
+use crate::spectrum::gl::{get_shader_source, Glsl};
+use crate::utils::base::BaseSpectrum;
+use crate::utils::color::{ColorSpace, ColorSpaceKind};
+use crate::utils::kernel::{Kernel, KernelKind};
+use crate::utils::render::Renderer;
+
+/// The number of Sources in each row of the source texture.
+const SOURCE_TEXTURE_WIDTH: u32 = 1024;
+
+/// A WebGL2 + WebAssembly implementation of Spectrum.
+///
+/// Unlike `SpectrumWebGL`, Sources are read from a float texture rather than a uniform array, so the number of Sources is not limited by the fragment shader's uniform vectors.
+///
+/// Below a render scale of 1, the Spectrum is drawn into an offscreen framebuffer at a fraction of the canvas resolution, which is then upsampled to the canvas.
+#[wasm_bindgen]
+pub struct SpectrumWebGL2 {
+    base: BaseSpectrum,
+    context: WebGl2RenderingContext,
+    program: WebGlProgram,
+    renderer: Renderer,
+    palette_texture: WebGlTexture,
+    source_texture: WebGlTexture,
+    framebuffer: WebGlFramebuffer,
+    frame_texture: WebGlTexture,
+    upsample_program: WebGlProgram,
+}
+
+#[wasm_bindgen]
+impl SpectrumWebGL2 {
+    /// Creates a new `SpectrumWebGL2`.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - the `SpectrumWebGL2`'s width.
+    /// * `height` - the `SpectrumWebGL2`'s height.
+    /// * `num_sources` - the number of Sources in the `SpectrumWebGL2`.
+    /// * `context` - the `webgl2` context belonging to the `SpectrumWebGL2`'s canvas.
+    /// * `movement_speed` - the range of each Source's movement speed (`dx`, `dy`)
+    /// * `color_speed` - the range of each Source's color speed (`dh`)
+    /// * `seed` - the seed to generate the Sources from, or `undefined` to pick one at random.
+    /// * `kernel` - the kind of `Kernel` weighting each Source by its distance from a pixel, or `undefined` for inverse square.
+    /// * `kernel_parameter` - the exponent, standard deviation, scale or radius of the `Kernel`, or `undefined` to use the kind's default.
+    /// * `color_space` - the kind of color space each pixel's Hue is mapped through, or `undefined` for fully saturated HSL.
+    /// * `color_lightness` - the HSL lightness, HSV value or Oklab lightness, or `undefined` to use the kind's default.
+    /// * `color_chroma` - the HSL or HSV saturation or the Oklab chroma, or `undefined` to use the kind's default.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the canvas does not provide a `webgl2` context or the shaders cannot be set up.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        width: u32,
+        height: u32,
+        num_sources: u32,
+        movement_speed: u32,
+        color_speed: u32,
+        source_dropoff: u32,
+        canvas: &HtmlCanvasElement,
+        seed: Option<u64>,
+        kernel: Option<KernelKind>,
+        kernel_parameter: Option<f32>,
+        color_space: Option<ColorSpaceKind>,
+        color_lightness: Option<f32>,
+        color_chroma: Option<f32>,
+    ) -> Result<Self, JsValue> {
+        let mut renderer = Renderer::new(source_dropoff);
+        if let Some(kernel) = kernel {
+            renderer.set_kernel(Kernel::new(kernel, kernel_parameter));
+        }
+        if let Some(color_space) = color_space {
+            renderer.set_color_space(ColorSpace::new(color_space, color_lightness, color_chroma));
+        }
+
+        Ok(Self::from_parts(
+            BaseSpectrum::new(
+                width,
+                height,
+                num_sources,
+                movement_speed,
+                color_speed,
+                seed,
+            ),
+            renderer,
+            canvas,
+        )?)
+    }
+
+    /// Draws to the Spectrum canvas, uploading the current state of its Sources to the source texture.
+    ///
+    /// Assigns Hues to each pixel based off of an average distance weighting across all Sources, using the current `Kernel`.
+    ///
+    /// As hue is a circular/periodic metric, a numerical average is inaccurate - instead, hue is broken into sine and cosine components which are summed and reconstructed into the resulting Hue.
+    ///
+    /// Below a render scale of 1, the Spectrum is drawn into the frame texture and then upsampled to the canvas.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the browser rejects the Sources uploaded to the source texture.
+    pub fn draw(&self) -> Result<(), SpectrumError> {
+        self.upload_sources()?;
+
+        let render_scale = self.renderer.render_scale();
+        let context = &self.context;
+        context.use_program(Some(&self.program));
+
+        let rows = if render_scale < 1. {
+            let (width, height) = self.frame_size();
+            context.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, Some(&self.framebuffer));
+            context.viewport(0, 0, width.cast_signed(), height.cast_signed());
+            height
+        } else {
+            self.base.height()
+        };
+
+        let num_sources_loc = context.get_uniform_location(&self.program, "num_sources");
+
+        let source_dropoff_info_loc = context.get_uniform_location(&self.program, "source_dropoff");
+
+        let height_loc = context.get_uniform_location(&self.program, "height");
+
+        let pixel_size_loc = context.get_uniform_location(&self.program, "pixel_size");
+
+        let kernel_parameter_loc = context.get_uniform_location(&self.program, "kernel_parameter");
+
+        let color_lightness_loc = context.get_uniform_location(&self.program, "color_lightness");
+
+        let color_chroma_loc = context.get_uniform_location(&self.program, "color_chroma");
+
+        context.uniform1i(
+            num_sources_loc.as_ref(),
+            (self.base.sources().len() as u32).cast_signed(),
+        );
+        context.uniform1f(
+            source_dropoff_info_loc.as_ref(),
+            self.renderer.source_dropoff_exponent(),
+        );
+        context.uniform1f(height_loc.as_ref(), rows as f32);
+        context.uniform1f(
+            pixel_size_loc.as_ref(),
+            self.base.pixel_size() / render_scale,
+        );
+        context.uniform1f(
+            kernel_parameter_loc.as_ref(),
+            self.renderer.kernel().parameter(),
+        );
+        context.uniform1f(
+            color_lightness_loc.as_ref(),
+            self.renderer.color_space().lightness(),
+        );
+        context.uniform1f(
+            color_chroma_loc.as_ref(),
+            self.renderer.color_space().chroma(),
+        );
+
+        context.draw_arrays(WebGl2RenderingContext::TRIANGLE_FAN, 0, 4);
+
+        if render_scale < 1. {
+            self.draw_upsampled();
+        }
+
+        Ok(())
+    }
+
+    /// Adds a randomly generated Source, keeping the existing Sources.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, without adding the Source, if the source texture cannot hold another Source.
+    #[allow(non_snake_case)]
+    pub fn addSource(&mut self) -> Result<(), JsValue> {
+        check_num_sources(&self.context, self.base.sources().len() as u32 + 1)?;
+        self.base.add_source();
+
+        Ok(())
+    }
+
+    /// Removes a Source, keeping the other Sources.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - the index of the Source to remove.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no Source at `index`.
+    #[allow(non_snake_case)]
+    pub fn removeSource(&mut self, index: u32) -> Result<(), JsValue> {
+        self.base
+            .remove_source(index as usize)
+            .ok_or(SpectrumError::NoSuchSource(index))?;
+
+        Ok(())
+    }
+
+    /// Replaces the state of a Source.
+    ///
+    /// Positions and speeds are in pixels, or fractions of the canvas diagonal if coordinates are normalized.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - the index of the Source to replace.
+    /// * `x` - the x-coordinate of the Source, which is clamped to the canvas.
+    /// * `y` - the y-coordinate of the Source, which is clamped to the canvas.
+    /// * `hue` - the Hue value of the Source, in radians.
+    /// * `dx` - the rate of movement in the x direction.
+    /// * `dy` - the rate of movement in the y direction.
+    /// * `dh` - the rate of change in the Source's Hue.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no Source at `index`.
+    #[allow(non_snake_case, clippy::too_many_arguments)]
+    pub fn setSource(
+        &mut self,
+        index: u32,
+        x: f32,
+        y: f32,
+        hue: f32,
+        dx: f32,
+        dy: f32,
+        dh: f32,
+    ) -> Result<(), JsValue> {
+        let source = self.base.create_source(x, y, hue, dx, dy, dh);
+        self.base
+            .set_source(index as usize, source)
+            .ok_or(SpectrumError::NoSuchSource(index))?;
+
+        Ok(())
+    }
+}
+
+impl SpectrumWebGL2 {
+    /// Creates a new `SpectrumWebGL2` from an existing `BaseSpectrum` and `Renderer`, drawing its first frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - the `SpectrumWebGL2`'s `BaseSpectrum`.
+    /// * `renderer` - the `Renderer` holding the `SpectrumWebGL2`'s drawing settings.
+    /// * `canvas` - the canvas to draw the `SpectrumWebGL2` to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the canvas does not provide a `webgl2` context, the source texture cannot hold the Sources or the shaders cannot be set up.
+    fn from_parts(
+        base: BaseSpectrum,
+        renderer: Renderer,
+        canvas: &HtmlCanvasElement,
+    ) -> Result<Self, SpectrumError> {
+        let context = canvas
+            .get_context("webgl2")
+            .ok()
+            .flatten()
+            .and_then(|context| context.dyn_into::<WebGl2RenderingContext>().ok())
+            .ok_or(SpectrumError::NoContext("webgl2"))?;
+        check_num_sources(&context, base.sources().len() as u32)?;
+
+        let vertex_coords = [-1_f32, -1_f32, 1_f32, -1_f32, 1_f32, 1_f32, -1_f32, 1_f32];
+
+        let buffer = context.create_buffer().ok_or(SpectrumError::ContextLost)?;
+        context.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(&buffer));
+
+        unsafe {
+            let vertex_array = js_sys::Float32Array::view(&vertex_coords);
+
+            context.buffer_data_with_array_buffer_view(
+                WebGl2RenderingContext::ARRAY_BUFFER,
+                &vertex_array,
+                WebGl2RenderingContext::STATIC_DRAW,
+            );
+        }
+
+        let program = create_program(&context, &renderer)?;
+
+        let palette_texture = create_texture(
+            &context,
+            WebGl2RenderingContext::TEXTURE0,
+            WebGl2RenderingContext::LINEAR,
+            WebGl2RenderingContext::REPEAT,
+        )?;
+
+        // Float textures cannot be filtered without an extension, and are only read with `texelFetch` anyway.
+        let source_texture = create_texture(
+            &context,
+            WebGl2RenderingContext::TEXTURE1,
+            WebGl2RenderingContext::NEAREST,
+            WebGl2RenderingContext::CLAMP_TO_EDGE,
+        )?;
+
+        // Each tap of the bicubic filter reads a single texel.
+        let frame_texture = create_texture(
+            &context,
+            WebGl2RenderingContext::TEXTURE2,
+            WebGl2RenderingContext::NEAREST,
+            WebGl2RenderingContext::CLAMP_TO_EDGE,
+        )?;
+        let framebuffer = create_framebuffer(&context, &frame_texture)?;
+
+        let upsample_program = link_program(&context, Glsl::Es3.upsample_shader())?;
+        context.uniform1i(
+            context
+                .get_uniform_location(&upsample_program, "frame")
+                .as_ref(),
+            2,
+        );
+        context.use_program(Some(&program));
+
+        let spectrum = Self {
+            base,
+            context,
+            program,
+            renderer,
+            palette_texture,
+            source_texture,
+            framebuffer,
+            frame_texture,
+            upsample_program,
+        };
+
+        spectrum.upload_palette()?;
+        spectrum.allocate_frame_texture()?;
+
+        spectrum.draw()?;
+
+        Ok(spectrum)
+    }
+
+    /// Uploads the position and Hue of each Source to the source texture, one RGBA texel per Source.
+    ///
+    /// Sources fill the texture row by row, `SOURCE_TEXTURE_WIDTH` at a time, with the last row padded out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the browser rejects the upload.
+    fn upload_sources(&self) -> Result<(), SpectrumError> {
+        let unit_size = self.base.unit_size();
+        let sources = self.base.sources();
+        let rows = (sources.len() as u32).div_ceil(SOURCE_TEXTURE_WIDTH).max(1);
+
+        let mut source_info: Vec<f32> = sources
+            .iter()
+            .flat_map(|source| {
+                [
+                    source.x() * unit_size,
+                    source.y() * unit_size,
+                    source.hue_cos(),
+                    source.hue_sin(),
+                ]
+            })
+            .collect();
+        source_info.resize((rows * SOURCE_TEXTURE_WIDTH * 4) as usize, 0.);
+
+        self.context
+            .active_texture(WebGl2RenderingContext::TEXTURE1);
+        self.context.bind_texture(
+            WebGl2RenderingContext::TEXTURE_2D,
+            Some(&self.source_texture),
+        );
+
+        unsafe {
+            let source_array = js_sys::Float32Array::view(&source_info);
+
+            self.context
+                .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_array_buffer_view(
+                    WebGl2RenderingContext::TEXTURE_2D,
+                    0,
+                    WebGl2RenderingContext::RGBA32F.cast_signed(),
+                    SOURCE_TEXTURE_WIDTH.cast_signed(),
+                    rows.cast_signed(),
+                    0,
+                    WebGl2RenderingContext::RGBA,
+                    WebGl2RenderingContext::FLOAT,
+                    Some(&source_array),
+                )
+                .map_err(|err| SpectrumError::draw(&err))?;
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the shader program with one matching the `Renderer`'s settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, keeping the current shader program, if the new one cannot be set up.
+    fn rebuild_program(&mut self) -> Result<(), SpectrumError> {
+        let program = create_program(&self.context, &self.renderer)?;
+
+        self.context.delete_program(Some(&self.program));
+        self.program = program;
+
+        Ok(())
+    }
+}
+
+spectrum_methods!(SpectrumWebGL2);
+gl_spectrum_methods!(
+    SpectrumWebGL2,
+    WebGl2RenderingContext,
+    WebGl2RenderingContext::RGBA8
+);
+
+gl_helpers!(WebGl2RenderingContext, Glsl::Es3);
+
+/// Compiles and links the Spectrum's shader program and makes it the context's current program.
+///
+/// # Parameters
+///
+/// * `context` - the `WebGL2` context.
+/// * `renderer` - the `Renderer` whose kinds of `Kernel` and `ColorSpace`, and whether it has a `Palette`, the fragment shader uses.
+///
+/// # Errors
+///
+/// Returns an error, leaving the context's current program in place, if a shader fails to compile or the program fails to link.
+fn create_program(
+    context: &WebGl2RenderingContext,
+    renderer: &Renderer,
+) -> Result<WebGlProgram, SpectrumError> {
+    let source_loop = format!(
+        r"
+                for (int i = 0; i < num_sources; i++) {{
+                    vec4 source = texelFetch(sources, ivec2(i % {SOURCE_TEXTURE_WIDTH}, i / {SOURCE_TEXTURE_WIDTH}), 0);"
+    );
+    let program = link_program(
+        context,
+        get_shader_source(
+            Glsl::Es3,
+            renderer,
+            r"
+            uniform highp sampler2D sources;
+            uniform int num_sources;",
+            &source_loop,
+        )
+        .as_str(),
+    )?;
+
+    // The palette is bound to the first texture unit and the Sources to the second.
+    context.uniform1i(
+        context.get_uniform_location(&program, "palette").as_ref(),
+        0,
+    );
+    context.uniform1i(
+        context.get_uniform_location(&program, "sources").as_ref(),
+        1,
+    );
+
+    Ok(program)
+}
+
+/// Checks that the source texture can hold a number of Sources, which is limited by the context's `MAX_TEXTURE_SIZE` rows.
+///
+/// # Parameters
+///
+/// * `context` - the `WebGL2` context.
+/// * `num_sources` - the number of Sources the source texture must hold.
+///
+/// # Errors
+///
+/// Returns an error if the source texture would need more rows than the device supports.
+fn check_num_sources(
+    context: &WebGl2RenderingContext,
+    num_sources: u32,
+) -> Result<(), SpectrumError> {
+    let max = context
+        .get_parameter(WebGl2RenderingContext::MAX_TEXTURE_SIZE)
+        .ok()
+        .and_then(|max_size| max_size.as_f64())
+        .map_or(u32::MAX, |max_size| {
+            (max_size as u32).saturating_mul(SOURCE_TEXTURE_WIDTH)
+        });
+
+    if num_sources > max {
+        return Err(SpectrumError::TooManySources {
+            sources: num_sources,
+            max,
+        });
+    }
+
+    Ok(())
+}
@@ -1,16 +1,50 @@
+use crate::utils::math;
 use crate::utils::panic;
-use rand::{rngs::OsRng, Rng};
+use crate::utils::pointer::{PointerMode, Pointers};
+use crate::utils::scene::SceneSource;
+use rand::{rngs::OsRng, Rng, SeedableRng};
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
 use std::f32::consts;
 use std::iter;
+use wasm_bindgen::prelude::*;
 
 const TWO_PI: f32 = consts::PI * 2_f32;
-const TWO_THIRDS_PI: f32 = consts::FRAC_PI_3 * 2_f32;
-const FOUR_THIRDS_PI: f32 = consts::FRAC_PI_3 * 4_f32;
-const FIVE_THIRDS_PI: f32 = consts::FRAC_PI_3 * 5_f32;
 pub const SOURCE_DROPOFF_FACTOR: f32 = 0.01;
 const MOVEMENT_SPEED_FACTOR: f32 = 0.2;
 const COLOR_SPEED_FACTOR: f32 = 0.002;
 
+/// The number of frames per second of real time that `BaseSpectrum::tick_by` advances by, matching a 60 Hz display.
+const FRAMES_PER_SECOND: f32 = 60.;
+
+/// The longest time `BaseSpectrum::tick_by` advances by in a single call, so a long pause does not stall the animation while it catches up.
+const MAX_TICK_SECONDS: f32 = 1.;
+
+/// The furthest `BaseSpectrum::seek` replays respawning Sources past the last edit, an hour at 60 frames per second, so seeking far ahead cannot stall the animation.
+const MAX_RESPAWN_SEEK_FRAMES: f64 = 216_000.;
+
+/// The diagonal, in pixels, of the 1920x1080 canvas which normalized Spectrums look the same as at every resolution.
+const REFERENCE_DIAGONAL: f32 = 2_202.907;
+
+/// The way Sources behave when they reach the edge of the Spectrum canvas.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BoundaryMode {
+    /// Sources bounce off of the edges like a mirror.
+    #[default]
+    Bounce = 0,
+
+    /// Sources leaving one edge reappear at the opposite edge.
+    Wrap = 1,
+
+    /// Sources stop moving along an axis once they reach an edge on it.
+    Clamp = 2,
+
+    /// Sources leaving the canvas are replaced by new random Sources entering from a random edge.
+    Respawn = 3,
+}
+
 /// Wrapper of three byte values corresponding to RGB for a single pixel.
 pub struct RGB(pub u8, pub u8, pub u8);
 
@@ -32,64 +66,10 @@ impl Hue {
     pub const fn get(self) -> f32 {
         self.0
     }
-
-    /// Increments the internal value by the specificied delta.
-    ///
-    /// If the new value lies outside the valid Hue range, it is adjusted accordingly by one period.
-    ///
-    /// # Arguments
-    ///
-    /// * `dh` - the desired change to the internal value.
-    fn tick(&mut self, dh: f32) {
-        self.0 = (self.0 + dh) % TWO_PI;
-    }
-
-    /// Converts the Hue to its corresponding RGB value.
-    ///
-    /// Sets saturation to 100% and lightness to 50% to get the Hue's truest color value.
-    ///
-    /// Derived from [`RapidTables` HSL to RGB color conversion](https://www.rapidtables.com/convert/color/hsl-to-rgb.html).
-    pub fn to_rgb(self) -> RGB {
-        let hue = self.0;
-        if hue < consts::PI {
-            if hue < consts::FRAC_PI_3 {
-                RGB(u8::MAX, (255_f32 * hue / consts::FRAC_PI_3) as u8, 0)
-            } else if hue < TWO_THIRDS_PI {
-                RGB(
-                    (255_f32 * (2_f32 - hue / consts::FRAC_PI_3)) as u8,
-                    u8::MAX,
-                    0,
-                )
-            } else {
-                RGB(
-                    0,
-                    u8::MAX,
-                    (255_f32 * (hue / consts::FRAC_PI_3 - 2_f32)) as u8,
-                )
-            }
-        } else if hue < FOUR_THIRDS_PI {
-            RGB(
-                0,
-                (255_f32 * (4_f32 - hue / consts::FRAC_PI_3)) as u8,
-                u8::MAX,
-            )
-        } else if hue < FIVE_THIRDS_PI {
-            RGB(
-                (255_f32 * (hue / consts::FRAC_PI_3 - 4_f32)) as u8,
-                0,
-                u8::MAX,
-            )
-        } else {
-            RGB(
-                u8::MAX,
-                0,
-                (255_f32 * (6_f32 - hue / consts::FRAC_PI_3)) as u8,
-            )
-        }
-    }
 }
 
 /// A Source in the Spectrum canvas which influences the color of neighboring pixels.
+#[derive(Clone)]
 pub struct Source {
     /// The x-coordinate of the Source in the Spectrum canvas.
     x: f32,
@@ -100,12 +80,6 @@ pub struct Source {
     /// The internal Hue value of the Source.
     hue: Hue,
 
-    /// The width of the Spectrum canvas.
-    canvas_width: f32,
-
-    /// The height of the spectrum canvas:
-    canvas_height: f32,
-
     /// The rate of movement in the x direction.
     dx: f32,
 
@@ -126,10 +100,72 @@ pub struct Source {
     dh_random: f32,
 }
 
+/// Rounds a speed to the nearest multiple of `step`, keeping its direction.
+///
+/// Speeds less than half of `step` are rounded to 0, so they never change by more than half of `step`.
+fn quantize_speed(speed: f32, step: f32) -> f32 {
+    if step <= 0. {
+        speed
+    } else {
+        (speed / step).round() * step
+    }
+}
+
+/// Returns the range of a Source's movement speed in coordinate units per frame.
+///
+/// # Arguments
+///
+/// * `movement_speed` - the range of the Source's movement speed (`dx`, `dy`)
+/// * `unit_size` - the size of one coordinate unit, in pixels of the reference canvas.
+fn get_movement_speed(movement_speed: u32, unit_size: f32) -> f32 {
+    (movement_speed as f32) * MOVEMENT_SPEED_FACTOR / unit_size
+}
+
+/// Returns the ratio between a new and an old length, or 0 if the old length is 0.
+fn get_ratio(new: f32, old: f32) -> f32 {
+    if old > 0. {
+        new / old
+    } else {
+        0.
+    }
+}
+
+/// Returns the size of a canvas in coordinate units - pixels, or fractions of its diagonal if it is normalized.
+///
+/// # Arguments
+///
+/// * `width` - the width of the canvas, in pixels.
+/// * `height` - the height of the canvas, in pixels.
+/// * `normalized` - whether the canvas uses normalized coordinates.
+#[must_use]
+pub fn canvas_size(width: u32, height: u32, normalized: bool) -> (f32, f32) {
+    let width_float = width as f32;
+    let height_float = height as f32;
+
+    if normalized {
+        let diagonal = width_float.hypot(height_float);
+        (
+            get_ratio(width_float, diagonal),
+            get_ratio(height_float, diagonal),
+        )
+    } else {
+        (width_float, height_float)
+    }
+}
+
 fn get_speed(input: f32, random: f32) -> f32 {
     input.mul_add(random, -input / 2.)
 }
 
+/// Inverts `get_speed`, returning the random factor in [0, 1] which produces `speed` from `input`.
+fn get_random(input: f32, speed: f32) -> f32 {
+    if input > 0. {
+        (speed / input + 0.5).clamp(0., 1.)
+    } else {
+        0.5
+    }
+}
+
 impl Source {
     /// Constructs a new Source.
     ///
@@ -141,31 +177,33 @@ impl Source {
     /// * `canvas_height` - the height of the Spectrum canvas.
     /// * `movement_speed` - the range of the Source's movement speed (`dx`, `dy`)
     /// * `color_speed` - the range of the Source's color speed (`dh`)
-    pub fn new(
+    /// * `unit_size` - the size of one coordinate unit, in pixels of the reference canvas.
+    /// * `rng` - the random number generator to draw the Source's parameters from.
+    pub fn new<R: Rng>(
         canvas_width: f32,
         canvas_height: f32,
         movement_speed: u32,
         color_speed: u32,
+        unit_size: f32,
+        rng: &mut R,
     ) -> Self {
-        let hue = Hue(OsRng.gen_range(0.0_f32..TWO_PI));
+        let hue = Hue(rng.gen_range(0.0_f32..TWO_PI));
         let hue_val = hue.get();
         let hue_cos = hue_val.cos();
         let hue_sin = hue_val.sin();
 
-        let movement_speed_float = (movement_speed as f32) * MOVEMENT_SPEED_FACTOR;
+        let movement_speed_float = get_movement_speed(movement_speed, unit_size);
         let color_speed_float = (color_speed as f32) * COLOR_SPEED_FACTOR;
 
-        let dx_random: f32 = OsRng.gen();
-        let dy_random: f32 = OsRng.gen();
-        let dh_random: f32 = OsRng.gen();
+        let dx_random: f32 = rng.gen();
+        let dy_random: f32 = rng.gen();
+        let dh_random: f32 = rng.gen();
 
         Self {
-            x: OsRng.gen_range(0.0_f32..canvas_width),
-            y: OsRng.gen_range(0.0_f32..canvas_height),
+            x: rng.gen_range(0.0_f32..canvas_width),
+            y: rng.gen_range(0.0_f32..canvas_height),
 
             hue,
-            canvas_width,
-            canvas_height,
             dx_random,
             dy_random,
             dh_random,
@@ -177,28 +215,111 @@ impl Source {
         }
     }
 
+    /// Restores a Source from a scene snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - the snapshot of the Source.
+    /// * `canvas_width` - the width of the Spectrum canvas.
+    /// * `canvas_height` - the height of the Spectrum canvas.
+    #[must_use]
+    pub fn from_scene(source: &SceneSource, canvas_width: f32, canvas_height: f32) -> Self {
+        let hue = Hue(source.hue.rem_euclid(TWO_PI));
+        let hue_val = hue.get();
+
+        Self {
+            x: source.x.clamp(0., canvas_width),
+            y: source.y.clamp(0., canvas_height),
+            hue,
+            dx: source.dx,
+            dy: source.dy,
+            dh: source.dh,
+            hue_cos: hue_val.cos(),
+            hue_sin: hue_val.sin(),
+            dx_random: source.dx_random,
+            dy_random: source.dy_random,
+            dh_random: source.dh_random,
+        }
+    }
+
+    /// Takes a scene snapshot of the Source.
+    #[must_use]
+    pub const fn to_scene(&self) -> SceneSource {
+        SceneSource {
+            x: self.x,
+            y: self.y,
+            hue: self.hue.get(),
+            dx: self.dx,
+            dy: self.dy,
+            dh: self.dh,
+            dx_random: self.dx_random,
+            dy_random: self.dy_random,
+            dh_random: self.dh_random,
+        }
+    }
+
     /// Returns the x-coordinate of the Source.
+    #[must_use]
     pub const fn x(&self) -> f32 {
         self.x
     }
 
     /// Returns the y-coordinate of the Source.
+    #[must_use]
     pub const fn y(&self) -> f32 {
         self.y
     }
 
     /// Returns the cosine of the Source's hue.
+    #[must_use]
     pub const fn hue_cos(&self) -> f32 {
         self.hue_cos
     }
 
     /// Returns the sine of the Source's hue.
+    #[must_use]
     pub const fn hue_sin(&self) -> f32 {
         self.hue_sin
     }
 
-    pub fn update_movement_speed(&mut self, movement_speed: u32) {
-        let movement_speed_float = (movement_speed as f32) * MOVEMENT_SPEED_FACTOR;
+    /// Moves the Source to a new position.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - the new x-coordinate of the Source.
+    /// * `y` - the new y-coordinate of the Source.
+    pub const fn set_position(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    /// Scales the Source's position and movement speed, such as when the canvas is resized.
+    ///
+    /// # Arguments
+    ///
+    /// * `x_scale` - the factor to scale the x-coordinate by.
+    /// * `y_scale` - the factor to scale the y-coordinate by.
+    /// * `speed_scale` - the factor to scale `dx` and `dy` by.
+    const fn rescale(&mut self, x_scale: f32, y_scale: f32, speed_scale: f32) {
+        self.x *= x_scale;
+        self.y *= y_scale;
+        self.dx *= speed_scale;
+        self.dy *= speed_scale;
+    }
+
+    /// Moves the Source back onto the canvas if it has left it.
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas_width` - the width of the Spectrum canvas.
+    /// * `canvas_height` - the height of the Spectrum canvas.
+    const fn clamp_position(&mut self, canvas_width: f32, canvas_height: f32) {
+        self.x = self.x.clamp(0., canvas_width);
+        self.y = self.y.clamp(0., canvas_height);
+    }
+
+    pub fn update_movement_speed(&mut self, movement_speed: u32, unit_size: f32) {
+        let movement_speed_float = get_movement_speed(movement_speed, unit_size);
 
         self.dx = self.dx.signum() * get_speed(movement_speed_float, self.dx_random).abs();
         self.dy = self.dy.signum() * get_speed(movement_speed_float, self.dy_random).abs();
@@ -210,39 +331,236 @@ impl Source {
         self.dh = self.dh.signum() * get_speed(color_speed_float, self.dh_random).abs();
     }
 
-    /// Increments the Source by one frame.
+    /// Rounds the Source's speeds so that it returns exactly to its current position and Hue after a number of frames.
     ///
-    /// The internal hue is incremented by the Source's `dh` value.
+    /// A bouncing Source returns after crossing the canvas there and back a whole number of times, and a wrapping Source after crossing it a whole number of times. Speeds which are too slow to cross the canvas within the period are rounded to 0, so short periods may make slow Sources stationary.
     ///
-    /// The Source's position is incremented by `dx` and `dy`, with border collisions behaving as a bounce.
-    fn tick(&mut self) {
-        self.hue.tick(self.dh);
-        let hue_val = self.hue.get();
-        self.hue_cos = hue_val.cos();
-        self.hue_sin = hue_val.sin();
+    /// # Arguments
+    ///
+    /// * `period` - the number of frames after which the Source returns.
+    /// * `boundary_mode` - the way the Source behaves when it reaches the edge of the canvas.
+    /// * `canvas_width` - the width of the Spectrum canvas.
+    /// * `canvas_height` - the height of the Spectrum canvas.
+    fn quantize_speeds(
+        &mut self,
+        period: f32,
+        boundary_mode: BoundaryMode,
+        canvas_width: f32,
+        canvas_height: f32,
+    ) {
+        let crossings = if boundary_mode == BoundaryMode::Wrap {
+            1_f32
+        } else {
+            2_f32
+        };
+
+        self.dx = quantize_speed(self.dx, canvas_width * crossings / period);
+        self.dy = quantize_speed(self.dy, canvas_height * crossings / period);
+        self.dh = quantize_speed(self.dh, TWO_PI / period);
+    }
 
-        self.x += self.dx;
-        self.y += self.dy;
+    /// Increments the Source by a number of frames.
+    ///
+    /// The internal hue is incremented by the Source's `dh` value per frame.
+    ///
+    /// The Source's position is incremented by `dx` and `dy` per frame, with border collisions handled according to `boundary_mode`.
+    ///
+    /// Returns whether the Source has left the canvas and should be respawned, which only happens with `BoundaryMode::Respawn`.
+    ///
+    /// # Arguments
+    ///
+    /// * `boundary_mode` - the way the Source behaves when it reaches the edge of the canvas.
+    /// * `frames` - the number of frames to move the Source by, which may be fractional.
+    /// * `canvas_width` - the width of the Spectrum canvas.
+    /// * `canvas_height` - the height of the Spectrum canvas.
+    fn tick(
+        &mut self,
+        boundary_mode: BoundaryMode,
+        frames: f32,
+        canvas_width: f32,
+        canvas_height: f32,
+    ) -> bool {
+        *self = self.at(
+            boundary_mode,
+            f64::from(frames),
+            canvas_width,
+            canvas_height,
+        );
 
-        if self.x <= 0_f32 {
-            self.x *= -1_f32;
-            self.dx *= -1_f32;
-        } else if self.x >= self.canvas_width {
-            self.x = self.canvas_width - (self.x - self.canvas_width);
-            self.dx *= -1_f32;
+        boundary_mode == BoundaryMode::Respawn
+            && (!(0_f32..=canvas_width).contains(&self.x)
+                || !(0_f32..=canvas_height).contains(&self.y))
+    }
+
+    /// Returns the Source as it will be after a number of frames, computed directly rather than by ticking it.
+    ///
+    /// Bouncing and wrapping Sources move linearly apart from their reflections, so their state can be found in closed form for any number of frames, including negative ones. Clamped Sources are found the same way, stopping along each axis once they reach an edge on it.
+    ///
+    /// Sources are never respawned, so with `BoundaryMode::Respawn` they carry on past the edges of the canvas.
+    ///
+    /// Positions and Hues are found in double precision and reduced to a single period before being stored, so they stay precise however many frames the Source moves by.
+    ///
+    /// # Arguments
+    ///
+    /// * `boundary_mode` - the way the Source behaves when it reaches the edge of the canvas.
+    /// * `frames` - the number of frames to move the Source by, which may be fractional or negative.
+    /// * `canvas_width` - the width of the Spectrum canvas.
+    /// * `canvas_height` - the height of the Spectrum canvas.
+    #[must_use]
+    fn at(
+        &self,
+        boundary_mode: BoundaryMode,
+        frames: f64,
+        canvas_width: f32,
+        canvas_height: f32,
+    ) -> Self {
+        let mut source = self.clone();
+
+        source.hue = Hue(reduce(
+            f64::from(self.dh).mul_add(frames, f64::from(self.hue.get())),
+            TWO_PI,
+        )
+        .rem_euclid(TWO_PI));
+        let hue_val = source.hue.get();
+        source.hue_cos = hue_val.cos();
+        source.hue_sin = hue_val.sin();
+
+        let x = f64::from(self.dx).mul_add(frames, f64::from(self.x));
+        let y = f64::from(self.dy).mul_add(frames, f64::from(self.y));
+
+        match boundary_mode {
+            BoundaryMode::Bounce => {
+                // Reducing by a whole period there and back keeps the direction the Source is moving in.
+                let (x, x_reversed) = math::reflect(reduce(x, canvas_width * 2.), canvas_width);
+                let (y, y_reversed) = math::reflect(reduce(y, canvas_height * 2.), canvas_height);
+                source.x = x;
+                source.y = y;
+
+                if x_reversed {
+                    source.dx *= -1_f32;
+                }
+                if y_reversed {
+                    source.dy *= -1_f32;
+                }
+            }
+            BoundaryMode::Wrap => {
+                source.x = math::wrap(reduce(x, canvas_width), canvas_width);
+                source.y = math::wrap(reduce(y, canvas_height), canvas_height);
+            }
+            BoundaryMode::Clamp => {
+                source.x = x.clamp(0., f64::from(canvas_width)) as f32;
+                source.y = y.clamp(0., f64::from(canvas_height)) as f32;
+
+                if !(0_f64..=f64::from(canvas_width)).contains(&x) {
+                    source.dx = 0_f32;
+                }
+                if !(0_f64..=f64::from(canvas_height)).contains(&y) {
+                    source.dy = 0_f32;
+                }
+            }
+            BoundaryMode::Respawn => {
+                source.x = x as f32;
+                source.y = y as f32;
+            }
         }
 
-        if self.y <= 0_f32 {
-            self.y *= -1_f32;
-            self.dy *= -1_f32;
-        } else if self.y >= self.canvas_height {
-            self.y = self.canvas_height - (self.y - self.canvas_height);
-            self.dy *= -1_f32;
+        source
+    }
+
+    /// Replaces the Source with a new random Source entering the canvas from a random edge.
+    ///
+    /// # Arguments
+    ///
+    /// * `movement_speed` - the range of the new Source's movement speed (`dx`, `dy`)
+    /// * `color_speed` - the range of the new Source's color speed (`dh`)
+    /// * `unit_size` - the size of one coordinate unit, in pixels of the reference canvas.
+    /// * `rng` - the random number generator to draw the new Source's parameters from.
+    /// * `canvas_width` - the width of the Spectrum canvas.
+    /// * `canvas_height` - the height of the Spectrum canvas.
+    fn respawn<R: Rng>(
+        &mut self,
+        movement_speed: u32,
+        color_speed: u32,
+        unit_size: f32,
+        rng: &mut R,
+        canvas_width: f32,
+        canvas_height: f32,
+    ) {
+        *self = Self::new(
+            canvas_width,
+            canvas_height,
+            movement_speed,
+            color_speed,
+            unit_size,
+            rng,
+        );
+
+        match rng.gen_range(0..4) {
+            0 => {
+                self.x = 0_f32;
+                self.dx = self.dx.abs();
+            }
+            1 => {
+                self.x = canvas_width;
+                self.dx = -self.dx.abs();
+            }
+            2 => {
+                self.y = 0_f32;
+                self.dy = self.dy.abs();
+            }
+            _ => {
+                self.y = canvas_height;
+                self.dy = -self.dy.abs();
+            }
         }
     }
 }
 
+/// The state of a `BaseSpectrum` at a frame which seeking is computed from.
+#[derive(Clone)]
+struct Anchor {
+    /// The frame the state was captured at.
+    frame: f64,
+
+    /// The `BaseSpectrum`'s Sources at the frame.
+    sources: Vec<Source>,
+
+    /// The `BaseSpectrum`'s random number generator at the frame, so respawned Sources can be replayed exactly.
+    rng: Pcg32,
+}
+
+/// Reduces a double precision coordinate or Hue into [0, `period`) before narrowing it to single precision.
+///
+/// Coordinates are left as they are if `period` is not positive.
+///
+/// # Arguments
+///
+/// * `value` - the coordinate or Hue.
+/// * `period` - the length after which the value repeats.
+fn reduce(value: f64, period: f32) -> f32 {
+    if period > 0. {
+        value.rem_euclid(f64::from(period)) as f32
+    } else {
+        value as f32
+    }
+}
+
+/// Splits a number of frames into steps of at most one frame.
+///
+/// # Arguments
+///
+/// * `frames` - the number of frames to split.
+fn substeps(frames: f64) -> impl Iterator<Item = f32> {
+    let frames = frames.max(0.);
+    let whole_frames = frames.floor();
+    let remaining_frames = (frames - whole_frames) as f32;
+
+    iter::repeat_n(1., whole_frames as usize)
+        .chain((remaining_frames > 0.).then_some(remaining_frames))
+}
+
 /// The shared data belonging to both Spectrum implementations.
+#[derive(Clone)]
 pub struct BaseSpectrum {
     /// The width of the Spectrum canvas.
     width: u32,
@@ -252,6 +570,38 @@ pub struct BaseSpectrum {
 
     /// A vector containing the Spectrum's sources.
     sources: Vec<Source>,
+
+    /// The seed used to generate the Spectrum's sources.
+    seed: u64,
+
+    /// The range of each Source's movement speed (`dx`, `dy`).
+    movement_speed: u32,
+
+    /// The range of each Source's color speed (`dh`).
+    color_speed: u32,
+
+    /// The random number generator new Sources are drawn from, continuing from the seed.
+    rng: Pcg32,
+
+    /// The pointers interacting with the `BaseSpectrum`'s Sources.
+    pointers: Pointers,
+
+    /// The way Sources behave when they reach the edge of the canvas.
+    boundary_mode: BoundaryMode,
+
+    /// The number of frames after which the `BaseSpectrum` returns to its current state, if it loops.
+    loop_period: Option<u32>,
+
+    /// Whether coordinates are fractions of the canvas diagonal rather than pixels.
+    normalized: bool,
+
+    /// The number of frames the `BaseSpectrum` has advanced by since it was created.
+    ///
+    /// This is kept in double precision so that it keeps counting, and seeking stays exact, long after single precision would stop resolving whole frames.
+    frame: f64,
+
+    /// The state the `BaseSpectrum` was last edited into, which its Sources move from.
+    anchor: Anchor,
 }
 
 impl BaseSpectrum {
@@ -264,59 +614,785 @@ impl BaseSpectrum {
     /// * `num_sources` - the number of Sources to generate.
     /// * `movement_speed` - the range of each Source's movement speed (`dx`, `dy`)
     /// * `color_speed` - the range of each Source's color speed (`dh`)
+    /// * `seed` - the seed to generate the Sources from, or `None` to pick one at random.
+    ///
+    /// The same seed and parameters always produce the same Sources, regardless of platform.
+    #[must_use]
     pub fn new(
         width: u32,
         height: u32,
         num_sources: u32,
         movement_speed: u32,
         color_speed: u32,
+        seed: Option<u64>,
     ) -> Self {
         panic::set_panic_hook();
 
         let width_float = width as f32;
         let height_float = height as f32;
 
+        let seed = seed.unwrap_or_else(|| OsRng.gen());
+        let mut rng = Pcg32::seed_from_u64(seed);
+
+        let sources = iter::repeat(())
+            .map(|()| {
+                Source::new(
+                    width_float,
+                    height_float,
+                    movement_speed,
+                    color_speed,
+                    1.,
+                    &mut rng,
+                )
+            })
+            .take(num_sources as usize)
+            .collect();
+
+        Self::from_parts(
+            width,
+            height,
+            movement_speed,
+            color_speed,
+            seed,
+            sources,
+            rng,
+        )
+    }
+
+    /// Constructs a `BaseSpectrum` from existing Sources.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - the width of the `BaseSpectrum`.
+    /// * `height` - the height of the `BaseSpectrum`.
+    /// * `movement_speed` - the range of each Source's movement speed (`dx`, `dy`)
+    /// * `color_speed` - the range of each Source's color speed (`dh`)
+    /// * `seed` - the seed the Sources were originally generated from.
+    /// * `sources` - the Sources of the `BaseSpectrum`.
+    /// * `normalized` - whether the Sources' coordinates are fractions of the canvas diagonal rather than pixels.
+    ///
+    /// Sources added later are drawn from the seed as if the existing Sources had been generated from it.
+    #[must_use]
+    pub fn from_sources(
+        width: u32,
+        height: u32,
+        movement_speed: u32,
+        color_speed: u32,
+        seed: u64,
+        sources: Vec<Source>,
+        normalized: bool,
+    ) -> Self {
+        panic::set_panic_hook();
+
+        let (canvas_width, canvas_height) = canvas_size(width, height, normalized);
+        let unit_size = if normalized { REFERENCE_DIAGONAL } else { 1. };
+
+        let mut rng = Pcg32::seed_from_u64(seed);
+        for _ in &sources {
+            Source::new(
+                canvas_width,
+                canvas_height,
+                movement_speed,
+                color_speed,
+                unit_size,
+                &mut rng,
+            );
+        }
+
+        let mut base = Self::from_parts(
+            width,
+            height,
+            movement_speed,
+            color_speed,
+            seed,
+            sources,
+            rng,
+        );
+        base.normalized = normalized;
+
+        base
+    }
+
+    /// Constructs a `BaseSpectrum` at frame 0 from its Sources and random number generator.
+    fn from_parts(
+        width: u32,
+        height: u32,
+        movement_speed: u32,
+        color_speed: u32,
+        seed: u64,
+        sources: Vec<Source>,
+        rng: Pcg32,
+    ) -> Self {
         Self {
             width,
             height,
-            sources: iter::repeat(())
-                .map(|()| Source::new(width_float, height_float, movement_speed, color_speed))
-                .take(num_sources as usize)
-                .collect(),
+            anchor: Anchor {
+                frame: 0.,
+                sources: sources.clone(),
+                rng: rng.clone(),
+            },
+            sources,
+            seed,
+            movement_speed,
+            color_speed,
+            rng,
+            pointers: Pointers::default(),
+            boundary_mode: BoundaryMode::Bounce,
+            loop_period: None,
+            normalized: false,
+            frame: 0.,
         }
     }
 
     /// Returns the width of the `BaseSpectrum`.
+    #[must_use]
     pub const fn width(&self) -> u32 {
         self.width
     }
 
     /// Returns the height of the `BaseSpectrum`.
+    #[must_use]
     pub const fn height(&self) -> u32 {
         self.height
     }
 
+    /// Resizes the `BaseSpectrum`, keeping its composition by moving each Source to the same relative position on the new canvas.
+    ///
+    /// Speeds are unchanged, apart from being rounded to the loop period again if there is one.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - the new width of the `BaseSpectrum`.
+    /// * `height` - the new height of the `BaseSpectrum`.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let (old_width, old_height) = self.canvas_size();
+        self.width = width;
+        self.height = height;
+        let (new_width, new_height) = self.canvas_size();
+
+        for source in &mut self.sources {
+            source.rescale(
+                get_ratio(new_width, old_width),
+                get_ratio(new_height, old_height),
+                1.,
+            );
+        }
+        self.clamp_sources();
+        if self.loop_period.is_some() {
+            self.reset_speeds();
+        }
+        self.set_anchor();
+    }
+
+    /// Returns whether coordinates are fractions of the canvas diagonal rather than pixels.
+    #[must_use]
+    pub const fn normalized(&self) -> bool {
+        self.normalized
+    }
+
+    /// Switches between pixel coordinates and normalized coordinates, keeping the Sources where they are.
+    ///
+    /// Normalized coordinates are fractions of the canvas diagonal, so the canvas spans `width / diagonal` by `height / diagonal`. Speeds, kernel parameters and pointer forces are then measured in pixels of a 1920x1080 canvas and scaled to the actual one, so the same parameters look the same at every resolution.
+    ///
+    /// # Arguments
+    ///
+    /// * `normalized` - whether to use normalized coordinates.
+    pub fn set_normalized(&mut self, normalized: bool) {
+        let (old_width, old_height) = self.canvas_size();
+        let old_unit_size = self.unit_size();
+        self.normalized = normalized;
+        let (new_width, new_height) = self.canvas_size();
+        let speed_scale = old_unit_size / self.unit_size();
+
+        for source in &mut self.sources {
+            source.rescale(
+                get_ratio(new_width, old_width),
+                get_ratio(new_height, old_height),
+                speed_scale,
+            );
+        }
+        self.clamp_sources();
+        if self.loop_period.is_some() {
+            self.reset_speeds();
+        }
+        self.set_anchor();
+    }
+
+    /// Returns the size of the canvas in coordinate units - pixels, or fractions of its diagonal if normalized.
+    #[must_use]
+    pub fn canvas_size(&self) -> (f32, f32) {
+        canvas_size(self.width, self.height, self.normalized)
+    }
+
+    /// Returns the size of one coordinate unit, in pixels of the reference canvas that speeds, kernel parameters and pointer forces are measured in.
+    ///
+    /// This is 1 with pixel coordinates, where the canvas is its own reference.
+    #[must_use]
+    pub const fn unit_size(&self) -> f32 {
+        if self.normalized {
+            REFERENCE_DIAGONAL
+        } else {
+            1.
+        }
+    }
+
+    /// Returns the size of one canvas pixel, in pixels of the reference canvas that speeds, kernel parameters and pointer forces are measured in.
+    ///
+    /// This is 1 with pixel coordinates, where the canvas is its own reference.
+    #[must_use]
+    pub fn pixel_size(&self) -> f32 {
+        if self.normalized {
+            get_ratio(
+                REFERENCE_DIAGONAL,
+                (self.width as f32).hypot(self.height as f32),
+            )
+        } else {
+            1.
+        }
+    }
+
+    /// Returns the seed used to generate the `BaseSpectrum`'s Sources.
+    #[must_use]
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the range of each Source's movement speed (`dx`, `dy`).
+    #[must_use]
+    pub const fn movement_speed(&self) -> u32 {
+        self.movement_speed
+    }
+
+    /// Returns the range of each Source's color speed (`dh`).
+    #[must_use]
+    pub const fn color_speed(&self) -> u32 {
+        self.color_speed
+    }
+
+    /// Returns the way Sources behave when they reach the edge of the canvas.
+    #[must_use]
+    pub const fn boundary_mode(&self) -> BoundaryMode {
+        self.boundary_mode
+    }
+
+    pub fn set_boundary_mode(&mut self, boundary_mode: BoundaryMode) {
+        self.boundary_mode = boundary_mode;
+        if self.loop_period.is_some() {
+            self.reset_speeds();
+        }
+        self.set_anchor();
+    }
+
+    /// Returns the number of frames after which the `BaseSpectrum` returns to its current state, if it loops.
+    #[must_use]
+    pub const fn loop_period(&self) -> Option<u32> {
+        self.loop_period
+    }
+
+    /// Makes the `BaseSpectrum` loop seamlessly, returning exactly to its current state after a number of frames, or stops it looping.
+    ///
+    /// Each Source's speeds are rounded to the nearest ones which bring it back to its current position and Hue after the period, so they stay close to the movement and color speeds over long periods. Sources too slow to cross the canvas within the period stop moving along it, and Sources whose Hue changes too slowly to cycle within it keep a fixed Hue, so short periods may make slow Sources stationary.
+    ///
+    /// Loops only hold while Sources bounce or wrap, and are broken by pointers.
+    ///
+    /// # Arguments
+    ///
+    /// * `loop_period` - the number of frames in the loop, or `None` to stop looping.
+    pub fn set_loop_period(&mut self, loop_period: Option<u32>) {
+        self.loop_period = loop_period.filter(|&loop_period| loop_period > 0);
+        self.reset_speeds();
+        self.set_anchor();
+    }
+
+    /// Sets the boundary mode and loop period of Sources restored from a scene.
+    ///
+    /// Unlike `set_boundary_mode` and `set_loop_period`, the Sources keep their speeds, which are only rounded to the loop period, so edited Sources are restored as they were.
+    ///
+    /// # Arguments
+    ///
+    /// * `boundary_mode` - the way Sources behave when they reach the edge of the canvas.
+    /// * `loop_period` - the number of frames in the loop, or `None` if the Sources do not loop.
+    pub fn restore_settings(&mut self, boundary_mode: BoundaryMode, loop_period: Option<u32>) {
+        self.boundary_mode = boundary_mode;
+        self.loop_period = loop_period.filter(|&loop_period| loop_period > 0);
+        self.quantize_speeds();
+        self.set_anchor();
+    }
+
     /// Returns a reference to the vector containing the `BaseSpectrum`'s Sources.
+    #[must_use]
     pub const fn sources(&self) -> &Vec<Source> {
         &self.sources
     }
 
     pub fn update_movement_speed(&mut self, movement_speed: u32) {
+        self.movement_speed = movement_speed;
+        let unit_size = self.unit_size();
         for source in &mut self.sources {
-            source.update_movement_speed(movement_speed);
+            source.update_movement_speed(movement_speed, unit_size);
         }
+        self.quantize_speeds();
+        self.set_anchor();
     }
 
     pub fn update_color_speed(&mut self, color_speed: u32) {
+        self.color_speed = color_speed;
         for source in &mut self.sources {
             source.update_color_speed(color_speed);
         }
+        self.quantize_speeds();
+        self.set_anchor();
+    }
+
+    /// Adds a randomly generated Source, drawn from the `BaseSpectrum`'s seed.
+    pub fn add_source(&mut self) {
+        let (canvas_width, canvas_height) = self.canvas_size();
+        let source = Source::new(
+            canvas_width,
+            canvas_height,
+            self.movement_speed,
+            self.color_speed,
+            self.unit_size(),
+            &mut self.rng,
+        );
+        self.sources.push(source);
+        self.quantize_speeds();
+        self.set_anchor();
+    }
+
+    /// Removes and returns the Source at `index`, or returns `None` if there is no such Source.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - the index of the Source to remove.
+    pub fn remove_source(&mut self, index: usize) -> Option<Source> {
+        (index < self.sources.len()).then(|| {
+            self.pointers.remove_source(index);
+            let source = self.sources.remove(index);
+            self.set_anchor();
+            source
+        })
+    }
+
+    /// Replaces the Source at `index`, returning the previous Source, or `None` if there is no such Source.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - the index of the Source to replace.
+    /// * `source` - the new Source.
+    pub fn set_source(&mut self, index: usize, source: Source) -> Option<Source> {
+        let previous = self
+            .sources
+            .get_mut(index)
+            .map(|existing| std::mem::replace(existing, source));
+        self.quantize_speeds();
+        self.set_anchor();
+        previous
+    }
+
+    /// Constructs a Source with the given state, which can be added to the `BaseSpectrum` with `set_source`.
+    ///
+    /// The position is clamped to the canvas, and the Source keeps its speeds relative to the `BaseSpectrum`'s movement and color speeds when those are updated.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - the x-coordinate of the Source.
+    /// * `y` - the y-coordinate of the Source.
+    /// * `hue` - the Hue value of the Source, in radians.
+    /// * `dx` - the rate of movement in the x direction.
+    /// * `dy` - the rate of movement in the y direction.
+    /// * `dh` - the rate of change in the Source's Hue.
+    #[must_use]
+    pub fn create_source(&self, x: f32, y: f32, hue: f32, dx: f32, dy: f32, dh: f32) -> Source {
+        let movement_speed_float = get_movement_speed(self.movement_speed, self.unit_size());
+        let color_speed_float = (self.color_speed as f32) * COLOR_SPEED_FACTOR;
+        let (canvas_width, canvas_height) = self.canvas_size();
+
+        Source::from_scene(
+            &SceneSource {
+                x,
+                y,
+                hue,
+                dx,
+                dy,
+                dh,
+                dx_random: get_random(movement_speed_float, dx),
+                dy_random: get_random(movement_speed_float, dy),
+                dh_random: get_random(color_speed_float, dh),
+            },
+            canvas_width,
+            canvas_height,
+        )
+    }
+
+    /// Presses a pointer on the `BaseSpectrum`, replacing any pointer with the same identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the identifier distinguishing the pointer from other simultaneous pointers, such as a touch identifier.
+    /// * `x` - the x-coordinate of the pointer in canvas pixels.
+    /// * `y` - the y-coordinate of the pointer in canvas pixels.
+    /// * `mode` - whether the pointer grabs the nearest Source, or attracts or repels nearby Sources.
+    pub fn pointer_down(&mut self, id: u32, x: f32, y: f32, mode: PointerMode) {
+        let scale = self.pixel_size() / self.unit_size();
+        self.pointers
+            .down(id, x * scale, y * scale, mode, &mut self.sources);
+        self.clamp_sources();
+        self.set_anchor();
+    }
+
+    /// Moves a pressed pointer, dragging any Source it has grabbed.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the identifier of the pointer.
+    /// * `x` - the new x-coordinate of the pointer in canvas pixels.
+    /// * `y` - the new y-coordinate of the pointer in canvas pixels.
+    pub fn pointer_move(&mut self, id: u32, x: f32, y: f32) {
+        let scale = self.pixel_size() / self.unit_size();
+        self.pointers
+            .move_to(id, x * scale, y * scale, &mut self.sources);
+        self.clamp_sources();
+        self.set_anchor();
+    }
+
+    /// Releases a pointer, letting go of any Source it has grabbed.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the identifier of the pointer.
+    pub fn pointer_up(&mut self, id: u32) {
+        self.pointers.up(id);
+    }
+
+    /// Sets how strongly attracting and repelling pointers move Sources.
+    ///
+    /// Both are measured in pixels of the reference canvas, which is the canvas itself unless coordinates are normalized.
+    ///
+    /// # Arguments
+    ///
+    /// * `strength` - the distance, in pixels per frame, that Sources directly beneath a pointer are moved.
+    /// * `radius` - the distance, in pixels, beyond which a pointer has no effect.
+    pub const fn set_pointer_force(&mut self, strength: f32, radius: f32) {
+        self.pointers.set_force(strength, radius);
+    }
+
+    /// Returns the number of frames the `BaseSpectrum` has advanced by since it was created.
+    #[must_use]
+    pub const fn frame(&self) -> f64 {
+        self.frame
     }
 
     /// Increments the `BaseSpectrum`'s sources by one frame.
     pub fn tick(&mut self) {
+        self.step(1.);
+    }
+
+    /// Increments the `BaseSpectrum`'s sources by an elapsed time, so the animation runs at the same speed regardless of frame rate.
+    ///
+    /// One second corresponds to 60 calls to `tick`. Long times are advanced in steps of at most one frame, so fast Sources still collide with the edges and pointers correctly, and are capped at one second.
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - the elapsed time, in seconds.
+    pub fn tick_by(&mut self, dt: f32) {
+        for frames in substeps(f64::from(
+            dt.clamp(0., MAX_TICK_SECONDS) * FRAMES_PER_SECOND,
+        )) {
+            self.step(frames);
+        }
+    }
+
+    /// Jumps the `BaseSpectrum`'s sources directly to a frame, forwards or backwards.
+    ///
+    /// Sources move from their state at the last edit - adding, removing or replacing Sources, changing speeds or the boundary mode, or pressing pointers - so seeking before it extrapolates the current Sources backwards. The state is computed in closed form, taking the same time for any frame, except with `BoundaryMode::Respawn`, where the frames since the last edit are replayed one at a time. Seeking before the last edit then stops at it, and seeking more than `MAX_RESPAWN_SEEK_FRAMES` (an hour at 60 frames per second) past where the replay starts stops there.
+    ///
+    /// Pointers are not applied while seeking.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - the frame to jump to, which may be fractional.
+    pub fn seek(&mut self, frame: f64) {
+        if !frame.is_finite() {
+            return;
+        }
+
+        if self.boundary_mode == BoundaryMode::Respawn {
+            if frame < self.frame {
+                self.sources.clone_from(&self.anchor.sources);
+                self.rng = self.anchor.rng.clone();
+                self.frame = self.anchor.frame;
+            }
+
+            let frame = frame.min(self.frame + MAX_RESPAWN_SEEK_FRAMES);
+            for frames in substeps(frame - self.frame) {
+                self.advance(frames);
+            }
+        } else {
+            let mut frames = frame - self.anchor.frame;
+            if let Some(loop_period) = self.loop_period.filter(|_| self.is_looping()) {
+                // Looping Sources return exactly to the anchor rather than accumulating rounding errors.
+                frames = frames.rem_euclid(f64::from(loop_period));
+            }
+            let (canvas_width, canvas_height) = self.canvas_size();
+            for (source, anchor) in self.sources.iter_mut().zip(&self.anchor.sources) {
+                *source = anchor.at(self.boundary_mode, frames, canvas_width, canvas_height);
+            }
+            self.frame = frame;
+        }
+    }
+
+    /// Jumps the `BaseSpectrum`'s sources directly to a time, forwards or backwards, where one second corresponds to 60 frames.
+    ///
+    /// # Arguments
+    ///
+    /// * `seconds` - the time to jump to.
+    pub fn seek_time(&mut self, seconds: f64) {
+        self.seek(seconds * f64::from(FRAMES_PER_SECOND));
+    }
+
+    /// Increments the `BaseSpectrum`'s sources by a number of frames, no greater than one, and applies its pointers.
+    ///
+    /// Looping Sources are found in closed form within the loop, so they never drift from it. Other Sources are moved on from their current state.
+    ///
+    /// # Arguments
+    ///
+    /// * `frames` - the number of frames to advance by.
+    fn step(&mut self, frames: f32) {
+        if self.is_looping() {
+            self.seek(self.frame + f64::from(frames));
+        } else {
+            self.advance(frames);
+        }
+
+        if !self.pointers.is_empty() {
+            let unit_size = self.unit_size();
+            self.pointers.tick(&mut self.sources, frames, unit_size);
+            self.clamp_sources();
+            self.set_anchor();
+        }
+    }
+
+    /// Increments the `BaseSpectrum`'s sources by a number of frames, no greater than one, respawning any which leave the canvas.
+    ///
+    /// # Arguments
+    ///
+    /// * `frames` - the number of frames to advance by.
+    fn advance(&mut self, frames: f32) {
+        let (canvas_width, canvas_height) = self.canvas_size();
+        let unit_size = self.unit_size();
+
         for source in &mut self.sources {
-            source.tick();
+            if source.tick(self.boundary_mode, frames, canvas_width, canvas_height) {
+                source.respawn(
+                    self.movement_speed,
+                    self.color_speed,
+                    unit_size,
+                    &mut self.rng,
+                    canvas_width,
+                    canvas_height,
+                );
+            }
         }
+
+        self.frame += f64::from(frames);
+    }
+
+    /// Returns whether the `BaseSpectrum` has a loop period and a boundary mode which keeps its Sources in the loop.
+    const fn is_looping(&self) -> bool {
+        self.loop_period.is_some()
+            && matches!(
+                self.boundary_mode,
+                BoundaryMode::Bounce | BoundaryMode::Wrap
+            )
+    }
+
+    /// Recomputes each Source's speeds from the movement and color speeds, then rounds them to the loop period.
+    fn reset_speeds(&mut self) {
+        let unit_size = self.unit_size();
+        for source in &mut self.sources {
+            source.update_movement_speed(self.movement_speed, unit_size);
+            source.update_color_speed(self.color_speed);
+        }
+        self.quantize_speeds();
+    }
+
+    /// Rounds each Source's speeds so that it returns to its current state after the loop period, if there is one.
+    fn quantize_speeds(&mut self) {
+        if let Some(loop_period) = self.loop_period {
+            let (canvas_width, canvas_height) = self.canvas_size();
+            for source in &mut self.sources {
+                source.quantize_speeds(
+                    loop_period as f32,
+                    self.boundary_mode,
+                    canvas_width,
+                    canvas_height,
+                );
+            }
+        }
+    }
+
+    /// Moves any Sources which pointers have pushed off of the canvas back onto it.
+    fn clamp_sources(&mut self) {
+        let (canvas_width, canvas_height) = self.canvas_size();
+        for source in &mut self.sources {
+            source.clamp_position(canvas_width, canvas_height);
+        }
+    }
+
+    /// Makes the current state the one Sources move from, after it has been edited.
+    fn set_anchor(&mut self) {
+        self.anchor = Anchor {
+            frame: self.frame,
+            sources: self.sources.clone(),
+            rng: self.rng.clone(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDARY_MODES: [BoundaryMode; 4] = [
+        BoundaryMode::Bounce,
+        BoundaryMode::Wrap,
+        BoundaryMode::Clamp,
+        BoundaryMode::Respawn,
+    ];
+
+    fn spectrum(boundary_mode: BoundaryMode) -> BaseSpectrum {
+        let mut base = BaseSpectrum::new(160, 90, 12, 40, 30, Some(7));
+        base.set_boundary_mode(boundary_mode);
+        base
+    }
+
+    fn assert_sources_eq(a: &BaseSpectrum, b: &BaseSpectrum, tolerance: f32) {
+        assert_eq!(a.sources.len(), b.sources.len());
+        for (a, b) in a.sources.iter().zip(&b.sources) {
+            assert!((a.x - b.x).abs() <= tolerance, "x: {} != {}", a.x, b.x);
+            assert!((a.y - b.y).abs() <= tolerance, "y: {} != {}", a.y, b.y);
+            assert!((a.hue_cos - b.hue_cos).abs() <= tolerance);
+            assert!((a.hue_sin - b.hue_sin).abs() <= tolerance);
+        }
+    }
+
+    #[test]
+    fn same_seed_generates_same_sources() {
+        let a = BaseSpectrum::new(160, 90, 12, 40, 30, Some(42));
+        let b = BaseSpectrum::new(160, 90, 12, 40, 30, Some(42));
+        let other = BaseSpectrum::new(160, 90, 12, 40, 30, Some(43));
+        let bits = |source: &Source| {
+            [
+                source.x,
+                source.y,
+                source.hue.get(),
+                source.dx,
+                source.dy,
+                source.dh,
+            ]
+            .map(f32::to_bits)
+        };
+
+        assert_eq!(a.seed(), 42);
+        assert_eq!(b.seed(), 42);
+        assert_eq!(
+            a.sources.iter().map(bits).collect::<Vec<_>>(),
+            b.sources.iter().map(bits).collect::<Vec<_>>()
+        );
+        assert_ne!(
+            a.sources.iter().map(bits).collect::<Vec<_>>(),
+            other.sources.iter().map(bits).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn seek_matches_ticking() {
+        for (boundary_mode, loop_period) in BOUNDARY_MODES
+            .into_iter()
+            .flat_map(|boundary_mode| [(boundary_mode, None), (boundary_mode, Some(120))])
+        {
+            let mut ticked = spectrum(boundary_mode);
+            ticked.set_loop_period(loop_period);
+            let mut seeked = ticked.clone();
+            for _ in 0..500 {
+                ticked.tick();
+            }
+            seeked.seek(500.);
+
+            assert!((ticked.frame() - seeked.frame()).abs() < f64::EPSILON);
+            assert_sources_eq(&ticked, &seeked, 1e-2);
+        }
+    }
+
+    #[test]
+    fn seek_round_trips() {
+        for boundary_mode in BOUNDARY_MODES {
+            let mut base = spectrum(boundary_mode);
+            base.seek(300.);
+            let expected = base.clone();
+
+            base.seek(-120.);
+            base.seek(45.);
+            base.seek(300.);
+
+            assert_sources_eq(&base, &expected, 1e-3);
+        }
+    }
+
+    #[test]
+    fn quantized_speeds_stay_within_one_step() {
+        let step = 0.3;
+        for speed in [
+            -2.9, -0.7, -0.16, -0.14, 0., 0.01, 0.149, 0.151, 0.44, 1., 5.55,
+        ] {
+            let quantized = quantize_speed(speed, step);
+            let steps = quantized / step;
+
+            assert!(
+                (quantized - speed).abs() <= step,
+                "{speed} became {quantized}"
+            );
+            assert!(
+                (steps - steps.round()).abs() < 1e-4,
+                "{quantized} is not a multiple of {step}"
+            );
+            assert!(quantized == 0. || quantized.signum() == speed.signum());
+        }
+        assert!(quantize_speed(0.1, step).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn seeking_far_keeps_precision() {
+        for boundary_mode in [BoundaryMode::Bounce, BoundaryMode::Wrap] {
+            let mut base = spectrum(boundary_mode);
+            base.seek(1e9);
+            let mut expected = base.clone();
+            for _ in 0..30 {
+                expected.tick();
+            }
+            base.seek(1e9 + 30.);
+
+            assert_sources_eq(&base, &expected, 1e-2);
+        }
+    }
+
+    #[test]
+    fn respawn_seeks_stop_an_hour_past_the_last_edit() {
+        let mut base = spectrum(BoundaryMode::Respawn);
+        base.seek(1e12);
+
+        assert!((base.frame() - MAX_RESPAWN_SEEK_FRAMES).abs() < 1e-6);
+    }
+
+    #[test]
+    fn ticking_keeps_counting_past_single_precision() {
+        let mut base = spectrum(BoundaryMode::Wrap);
+        base.seek(f64::from(1_u32 << 24));
+        let start = base.sources[0].clone();
+        base.tick();
+
+        assert!((base.frame() - f64::from((1_u32 << 24) + 1)).abs() < f64::EPSILON);
+        assert!((base.sources[0].x - start.x).abs() + (base.sources[0].y - start.y).abs() > 0.);
     }
 }
@@ -0,0 +1,548 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use std::f32::consts;
+
+use crate::utils::base::{self, BoundaryMode};
+use crate::utils::color::ColorSpaceKind;
+use crate::utils::kernel::KernelKind;
+use crate::utils::palette::PaletteSpace;
+use crate::utils::scene::{
+    Scene, SceneColorSpace, SceneError, SceneKernel, ScenePalette, SceneSource, SCENE_VERSION,
+};
+
+const TWO_PI: f32 = consts::PI * 2_f32;
+
+/// The current version of the scene code format.
+const CODE_VERSION: u8 = 3;
+
+/// The version of the scene code format before rendering settings, which is still accepted.
+const CODE_VERSION_SOURCES: u8 = 2;
+
+/// The version of the scene code format before normalized coordinates, which is still accepted.
+const CODE_VERSION_PIXELS: u8 = 1;
+
+/// The flag set in a scene code when its Sources use normalized coordinates.
+const FLAG_NORMALIZED: u8 = 1;
+
+/// The flag set in a scene code when it has a palette.
+const FLAG_PALETTE: u8 = 2;
+
+/// The flag set in a scene code when its palette's stops have positions.
+const FLAG_PALETTE_POSITIONS: u8 = 4;
+
+/// The number of bytes encoding each Source.
+const SOURCE_LENGTH: usize = 15;
+
+/// Reads values from the bytes of a scene code.
+struct CodeReader<'a> {
+    /// The bytes which have not been read yet.
+    bytes: &'a [u8],
+}
+
+impl CodeReader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], SceneError> {
+        if self.bytes.len() < N {
+            return Err(SceneError::InvalidCode("scene code is truncated"));
+        }
+        let mut taken = [0; N];
+        taken.copy_from_slice(&self.bytes[..N]);
+        self.bytes = &self.bytes[N..];
+
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, SceneError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, SceneError> {
+        Ok(u16::from_le_bytes(self.take()?))
+    }
+
+    fn i16(&mut self) -> Result<i16, SceneError> {
+        Ok(i16::from_le_bytes(self.take()?))
+    }
+
+    fn f32(&mut self) -> Result<f32, SceneError> {
+        Ok(f32::from_le_bytes(self.take()?))
+    }
+
+    /// Reads an unsigned LEB128 variable-length integer.
+    fn varint(&mut self) -> Result<u64, SceneError> {
+        let mut value = 0_u64;
+
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(SceneError::InvalidCode(
+            "scene code contains an oversized number",
+        ))
+    }
+
+    fn varint_u32(&mut self) -> Result<u32, SceneError> {
+        u32::try_from(self.varint()?)
+            .map_err(|_| SceneError::InvalidCode("scene code contains an oversized number"))
+    }
+
+    fn kernel_kind(&mut self) -> Result<KernelKind, SceneError> {
+        match self.u8()? {
+            0 => Ok(KernelKind::InversePower),
+            1 => Ok(KernelKind::Gaussian),
+            2 => Ok(KernelKind::Exponential),
+            3 => Ok(KernelKind::SmoothCompact),
+            _ => Err(SceneError::InvalidCode(
+                "scene code contains an unknown kernel",
+            )),
+        }
+    }
+
+    fn color_space_kind(&mut self) -> Result<ColorSpaceKind, SceneError> {
+        match self.u8()? {
+            0 => Ok(ColorSpaceKind::Hsl),
+            1 => Ok(ColorSpaceKind::Hsv),
+            2 => Ok(ColorSpaceKind::Oklch),
+            _ => Err(SceneError::InvalidCode(
+                "scene code contains an unknown color space",
+            )),
+        }
+    }
+
+    fn palette_space(&mut self) -> Result<PaletteSpace, SceneError> {
+        match self.u8()? {
+            0 => Ok(PaletteSpace::Srgb),
+            1 => Ok(PaletteSpace::LinearRgb),
+            2 => Ok(PaletteSpace::Oklab),
+            _ => Err(SceneError::InvalidCode(
+                "scene code contains an unknown palette color space",
+            )),
+        }
+    }
+
+    fn boundary_mode(&mut self) -> Result<BoundaryMode, SceneError> {
+        match self.u8()? {
+            0 => Ok(BoundaryMode::Bounce),
+            1 => Ok(BoundaryMode::Wrap),
+            2 => Ok(BoundaryMode::Clamp),
+            3 => Ok(BoundaryMode::Respawn),
+            _ => Err(SceneError::InvalidCode(
+                "scene code contains an unknown boundary mode",
+            )),
+        }
+    }
+}
+
+/// Writes an unsigned LEB128 variable-length integer.
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Quantizes a value in [0, `range`] to the full range of a `u16`.
+fn quantize_unsigned(value: f32, range: f32) -> u16 {
+    if range > 0. {
+        ((value / range).clamp(0., 1.) * f32::from(u16::MAX)).round() as u16
+    } else {
+        0
+    }
+}
+
+fn dequantize_unsigned(value: u16, range: f32) -> f32 {
+    f32::from(value) / f32::from(u16::MAX) * range
+}
+
+/// Quantizes a value in [-`range`, `range`] to the full range of an `i16`.
+fn quantize_signed(value: f32, range: f32) -> i16 {
+    if range > 0. {
+        ((value / range).clamp(-1., 1.) * f32::from(i16::MAX)).round() as i16
+    } else {
+        0
+    }
+}
+
+fn dequantize_signed(value: i16, range: f32) -> f32 {
+    f32::from(value) / f32::from(i16::MAX) * range
+}
+
+impl Scene {
+    /// Encodes the Scene as a compact, URL-safe string.
+    ///
+    /// Positions, hues and speeds are quantized to 16 bits and random factors to 8 bits, so decoding the code yields a Scene which is visually identical but not bit-for-bit equal to this one.
+    #[must_use]
+    pub fn to_code(&self) -> String {
+        let mut bytes = Vec::with_capacity(64 + self.sources.len() * SOURCE_LENGTH);
+        bytes.push(CODE_VERSION);
+        bytes.push(self.flags());
+
+        for value in [
+            self.width,
+            self.height,
+            self.movement_speed,
+            self.color_speed,
+            self.source_dropoff,
+            self.sources.len() as u32,
+        ] {
+            write_varint(&mut bytes, u64::from(value));
+        }
+        write_varint(&mut bytes, self.seed);
+
+        let movement_range = self.sources.iter().fold(0_f32, |max, source| {
+            max.max(source.dx.abs()).max(source.dy.abs())
+        });
+        let color_range = self
+            .sources
+            .iter()
+            .fold(0_f32, |max, source| max.max(source.dh.abs()));
+        bytes.extend_from_slice(&movement_range.to_le_bytes());
+        bytes.extend_from_slice(&color_range.to_le_bytes());
+        self.write_settings(&mut bytes);
+
+        let (width_float, height_float) =
+            base::canvas_size(self.width, self.height, self.normalized);
+
+        for source in &self.sources {
+            bytes.extend_from_slice(&quantize_unsigned(source.x, width_float).to_le_bytes());
+            bytes.extend_from_slice(&quantize_unsigned(source.y, height_float).to_le_bytes());
+            bytes.extend_from_slice(
+                &((source.hue.rem_euclid(TWO_PI) / TWO_PI * 65536.).round() as u32 as u16)
+                    .to_le_bytes(),
+            );
+            bytes.extend_from_slice(&quantize_signed(source.dx, movement_range).to_le_bytes());
+            bytes.extend_from_slice(&quantize_signed(source.dy, movement_range).to_le_bytes());
+            bytes.extend_from_slice(&quantize_signed(source.dh, color_range).to_le_bytes());
+            for random in [source.dx_random, source.dy_random, source.dh_random] {
+                bytes.push((random.clamp(0., 1.) * f32::from(u8::MAX)).round() as u8);
+            }
+        }
+
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    /// Decodes a Scene from a string produced by `to_code`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string is not a valid scene code of the current or an earlier version.
+    pub fn from_code(code: &str) -> Result<Self, SceneError> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(code.trim())
+            .map_err(|_| SceneError::InvalidCode("scene code is not valid base64"))?;
+        let mut reader = CodeReader { bytes: &bytes };
+
+        let version = reader.u8()?;
+        let flags = match version {
+            CODE_VERSION | CODE_VERSION_SOURCES => reader.u8()?,
+            CODE_VERSION_PIXELS => 0,
+            version => return Err(SceneError::UnsupportedVersion(u32::from(version))),
+        };
+        let normalized = flags & FLAG_NORMALIZED != 0;
+
+        let width = reader.varint_u32()?;
+        let height = reader.varint_u32()?;
+        let movement_speed = reader.varint_u32()?;
+        let color_speed = reader.varint_u32()?;
+        let source_dropoff = reader.varint_u32()?;
+        let num_sources = reader.varint_u32()? as usize;
+        let seed = reader.varint()?;
+
+        let movement_range = reader.f32()?;
+        let color_range = reader.f32()?;
+
+        let mut scene = Self {
+            version: SCENE_VERSION,
+            width,
+            height,
+            movement_speed,
+            color_speed,
+            source_dropoff,
+            seed,
+            normalized,
+            kernel: SceneKernel::default(),
+            color_space: SceneColorSpace::default(),
+            palette: None,
+            boundary_mode: BoundaryMode::default(),
+            loop_period: None,
+            render_scale: 1.,
+            far_field_tolerance: None,
+            sources: Vec::new(),
+        };
+        if version == CODE_VERSION {
+            scene.read_settings(&mut reader, flags)?;
+        }
+
+        if num_sources.checked_mul(SOURCE_LENGTH) != Some(reader.bytes.len()) {
+            return Err(SceneError::InvalidCode(
+                "scene code does not match its number of sources",
+            ));
+        }
+
+        let (width_float, height_float) = base::canvas_size(width, height, normalized);
+
+        scene.sources = (0..num_sources)
+            .map(|_| {
+                Ok(SceneSource {
+                    x: dequantize_unsigned(reader.u16()?, width_float),
+                    y: dequantize_unsigned(reader.u16()?, height_float),
+                    hue: f32::from(reader.u16()?) / 65536. * TWO_PI,
+                    dx: dequantize_signed(reader.i16()?, movement_range),
+                    dy: dequantize_signed(reader.i16()?, movement_range),
+                    dh: dequantize_signed(reader.i16()?, color_range),
+                    dx_random: f32::from(reader.u8()?) / f32::from(u8::MAX),
+                    dy_random: f32::from(reader.u8()?) / f32::from(u8::MAX),
+                    dh_random: f32::from(reader.u8()?) / f32::from(u8::MAX),
+                })
+            })
+            .collect::<Result<_, SceneError>>()?;
+        scene.validate()?;
+
+        Ok(scene)
+    }
+
+    /// Returns the flags describing the Scene's coordinates and palette.
+    const fn flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.normalized {
+            flags |= FLAG_NORMALIZED;
+        }
+        if let Some(palette) = &self.palette {
+            flags |= FLAG_PALETTE;
+            if palette.positions.is_some() {
+                flags |= FLAG_PALETTE_POSITIONS;
+            }
+        }
+
+        flags
+    }
+
+    /// Writes the Scene's rendering settings, boundary mode and loop period.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - the bytes of the scene code to append to.
+    fn write_settings(&self, bytes: &mut Vec<u8>) {
+        bytes.push(self.kernel.kind as u8);
+        bytes.extend_from_slice(&self.kernel.parameter.to_le_bytes());
+        bytes.push(self.color_space.kind as u8);
+        bytes.extend_from_slice(&self.color_space.lightness.to_le_bytes());
+        bytes.extend_from_slice(&self.color_space.chroma.to_le_bytes());
+        bytes.push(self.boundary_mode as u8);
+        write_varint(bytes, u64::from(self.loop_period.unwrap_or(0)));
+        bytes.extend_from_slice(&self.render_scale.to_le_bytes());
+        bytes.extend_from_slice(&self.far_field_tolerance.unwrap_or(0.).to_le_bytes());
+
+        if let Some(palette) = &self.palette {
+            bytes.push(palette.space as u8);
+            write_varint(bytes, palette.colors.len() as u64);
+            for color in &palette.colors {
+                bytes.extend_from_slice(&color.to_be_bytes()[1..]);
+            }
+            for position in palette.positions.iter().flatten() {
+                bytes.extend_from_slice(&position.to_le_bytes());
+            }
+        }
+    }
+
+    /// Reads the rendering settings, boundary mode and loop period written by `write_settings`.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - the reader of the scene code's bytes.
+    /// * `flags` - the flags describing the Scene's coordinates and palette.
+    fn read_settings(&mut self, reader: &mut CodeReader, flags: u8) -> Result<(), SceneError> {
+        self.kernel = SceneKernel {
+            kind: reader.kernel_kind()?,
+            parameter: reader.f32()?,
+        };
+        self.color_space = SceneColorSpace {
+            kind: reader.color_space_kind()?,
+            lightness: reader.f32()?,
+            chroma: reader.f32()?,
+        };
+        self.boundary_mode = reader.boundary_mode()?;
+        self.loop_period = Some(reader.varint_u32()?).filter(|&loop_period| loop_period > 0);
+        self.render_scale = reader.f32()?;
+        self.far_field_tolerance = Some(reader.f32()?).filter(|&tolerance| tolerance > 0.);
+
+        if flags & FLAG_PALETTE != 0 {
+            let space = reader.palette_space()?;
+            let num_colors = reader.varint_u32()?;
+            let colors = (0..num_colors)
+                .map(|_| {
+                    let [r, g, b] = reader.take()?;
+                    Ok(u32::from_be_bytes([0, r, g, b]))
+                })
+                .collect::<Result<Vec<_>, SceneError>>()?;
+            let positions = if flags & FLAG_PALETTE_POSITIONS == 0 {
+                None
+            } else {
+                Some(
+                    (0..num_colors)
+                        .map(|_| reader.f32())
+                        .collect::<Result<_, SceneError>>()?,
+                )
+            };
+
+            self.palette = Some(ScenePalette {
+                colors,
+                positions,
+                space,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::scene::tests::scene;
+
+    /// Returns the bytes of a Scene's code.
+    fn bytes(scene: &Scene) -> Vec<u8> {
+        URL_SAFE_NO_PAD.decode(scene.to_code()).unwrap()
+    }
+
+    /// Decodes a Scene from the bytes of a code, returning the reason it is invalid.
+    fn invalid_code(bytes: &[u8]) -> &'static str {
+        match Scene::from_code(&URL_SAFE_NO_PAD.encode(bytes)) {
+            Err(SceneError::InvalidCode(reason)) => reason,
+            Err(err) => panic!("unexpected error: {err}"),
+            Ok(_) => panic!("invalid scene code was accepted"),
+        }
+    }
+
+    #[test]
+    fn code_round_trips_within_quantization() {
+        for normalized in [false, true] {
+            let mut scene = scene(normalized);
+            let mut decoded = Scene::from_code(&scene.to_code()).unwrap();
+
+            let (width, height) = base::canvas_size(scene.width, scene.height, scene.normalized);
+            let movement_range = scene.sources.iter().fold(0_f32, |max, source| {
+                max.max(source.dx.abs()).max(source.dy.abs())
+            });
+            let color_range = scene
+                .sources
+                .iter()
+                .fold(0_f32, |max, source| max.max(source.dh.abs()));
+            // Each value is rounded to the nearest of its evenly spaced steps, with some slack for single precision.
+            let within = |a: f32, b: f32, range: f32, steps: u16| {
+                (a - b).abs() <= (range / f32::from(steps)).mul_add(0.5, range * 1e-6)
+            };
+
+            assert_eq!(decoded.sources.len(), scene.sources.len());
+            for (source, decoded) in scene.sources.iter().zip(&decoded.sources) {
+                assert!(within(source.x, decoded.x, width, u16::MAX));
+                assert!(within(source.y, decoded.y, height, u16::MAX));
+                let hue_difference = (source.hue - decoded.hue).rem_euclid(TWO_PI);
+                assert!(within(
+                    hue_difference.min(TWO_PI - hue_difference),
+                    0.,
+                    TWO_PI,
+                    u16::MAX
+                ));
+                assert!(within(
+                    source.dx,
+                    decoded.dx,
+                    movement_range,
+                    i16::MAX as u16
+                ));
+                assert!(within(
+                    source.dy,
+                    decoded.dy,
+                    movement_range,
+                    i16::MAX as u16
+                ));
+                assert!(within(source.dh, decoded.dh, color_range, i16::MAX as u16));
+                for (random, decoded) in [
+                    (source.dx_random, decoded.dx_random),
+                    (source.dy_random, decoded.dy_random),
+                    (source.dh_random, decoded.dh_random),
+                ] {
+                    assert!(within(random, decoded, 1., u16::from(u8::MAX)));
+                }
+            }
+
+            // Everything apart from the Sources is stored exactly.
+            scene.sources.clear();
+            decoded.sources.clear();
+            assert_eq!(decoded.to_json(), scene.to_json());
+        }
+    }
+
+    #[test]
+    fn truncated_codes_are_rejected() {
+        let bytes = bytes(&scene(true));
+
+        for len in 1..bytes.len() {
+            invalid_code(&bytes[..len]);
+        }
+        assert!(matches!(
+            Scene::from_code(""),
+            Err(SceneError::InvalidCode("scene code is truncated"))
+        ));
+    }
+
+    #[test]
+    fn oversized_codes_are_rejected() {
+        let mut bytes = bytes(&scene(false));
+        bytes.push(0);
+        assert_eq!(
+            invalid_code(&bytes),
+            "scene code does not match its number of sources"
+        );
+
+        // A number of Sources whose length does not fit in a 32-bit address space.
+        let mut bytes = vec![CODE_VERSION_SOURCES, 0];
+        for value in [1, 1, 1, 1, 1, u64::from(u32::MAX), 0] {
+            write_varint(&mut bytes, value);
+        }
+        bytes.extend([0; 8]);
+        assert_eq!(
+            invalid_code(&bytes),
+            "scene code does not match its number of sources"
+        );
+
+        // A width which does not fit in 32 bits.
+        let mut bytes = vec![CODE_VERSION, 0];
+        write_varint(&mut bytes, u64::from(u32::MAX) + 1);
+        assert_eq!(
+            invalid_code(&bytes),
+            "scene code contains an oversized number"
+        );
+
+        // A number which does not fit in 64 bits.
+        let mut bytes = vec![CODE_VERSION, 0];
+        bytes.extend([0xff; 10]);
+        assert_eq!(
+            invalid_code(&bytes),
+            "scene code contains an oversized number"
+        );
+
+        assert!(matches!(
+            Scene::from_code("not a scene code!"),
+            Err(SceneError::InvalidCode("scene code is not valid base64"))
+        ));
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        let mut bytes = bytes(&scene(false));
+
+        for version in [0, CODE_VERSION + 1, u8::MAX] {
+            bytes[0] = version;
+            assert!(matches!(
+                Scene::from_code(&URL_SAFE_NO_PAD.encode(&bytes)),
+                Err(SceneError::UnsupportedVersion(unsupported)) if unsupported == u32::from(version)
+            ));
+        }
+    }
+}
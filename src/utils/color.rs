@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+use std::f32::consts;
+use wasm_bindgen::prelude::*;
+
+use crate::utils::base::{Hue, RGB};
+
+/// The color space each pixel's Hue is mapped through to get its RGB value.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ColorSpaceKind {
+    /// HSL, where the lightness is the HSL lightness and the chroma is the HSL saturation - the original fully saturated look.
+    Hsl = 0,
+
+    /// HSV, where the lightness is the HSV value and the chroma is the HSV saturation.
+    Hsv = 1,
+
+    /// OKLCH, the polar form of the perceptually uniform Oklab, where the lightness is the Oklab lightness and the chroma is the Oklab chroma.
+    ///
+    /// Colors outside of the sRGB gamut are clipped.
+    Oklch = 2,
+}
+
+impl ColorSpaceKind {
+    /// Returns the lightness used when none is specified.
+    #[must_use]
+    pub const fn default_lightness(self) -> f32 {
+        match self {
+            Self::Hsl => 0.5,
+            Self::Hsv => 1.,
+            Self::Oklch => 0.75,
+        }
+    }
+
+    /// Returns the chroma used when none is specified.
+    #[must_use]
+    pub const fn default_chroma(self) -> f32 {
+        match self {
+            Self::Hsl | Self::Hsv => 1.,
+            Self::Oklch => 0.125,
+        }
+    }
+
+    /// Returns the body of the GLSL function `vec3 hue_to_rgb(float hue)`, computing the same color as `ColorSpace::to_rgb`.
+    ///
+    /// The `ColorSpace`'s lightness and chroma are read from the `color_lightness` and `color_chroma` uniforms, so they can change without recompiling the shader.
+    #[must_use]
+    pub const fn glsl(self) -> &'static str {
+        match self {
+            Self::Hsl => {
+                "float chroma = (1.0 - abs(2.0 * color_lightness - 1.0)) * color_chroma;
+                return color_lightness + chroma * (pure_hue(hue) - 0.5);"
+            }
+            Self::Hsv => {
+                "float chroma = color_lightness * color_chroma;
+                return color_lightness + chroma * (pure_hue(hue) - 1.0);"
+            }
+            Self::Oklch => {
+                "float a = color_chroma * cos(hue);
+                float b = color_chroma * sin(hue);
+
+                float l = pow(color_lightness + 0.3963377774 * a + 0.2158037573 * b, 3.0);
+                float m = pow(color_lightness - 0.1055613458 * a - 0.0638541728 * b, 3.0);
+                float s = pow(color_lightness - 0.0894841775 * a - 1.2914855480 * b, 3.0);
+
+                vec3 linear = clamp(vec3(
+                    4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+                    -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+                    -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s
+                ), 0.0, 1.0);
+
+                return mix(
+                    linear * 12.92,
+                    1.055 * pow(linear, vec3(1.0 / 2.4)) - 0.055,
+                    step(0.0031308, linear)
+                );"
+            }
+        }
+    }
+}
+
+/// A color space mapping Hues to RGB values, shared by the CPU renderer and the generated `WebGL` shader.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorSpace {
+    /// The color space Hues are mapped through.
+    kind: ColorSpaceKind,
+
+    /// The HSL lightness, HSV value or Oklab lightness, in [0, 1].
+    lightness: f32,
+
+    /// The HSL or HSV saturation, in [0, 1], or the Oklab chroma, in [0, 0.4].
+    chroma: f32,
+}
+
+impl Default for ColorSpace {
+    /// Returns the original fully saturated HSL color space.
+    fn default() -> Self {
+        Self::new(ColorSpaceKind::Hsl, None, None)
+    }
+}
+
+impl ColorSpace {
+    /// Constructs a new `ColorSpace`.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - the color space Hues are mapped through.
+    /// * `lightness` - the HSL lightness, HSV value or Oklab lightness, or `None` to use the kind's default.
+    /// * `chroma` - the HSL or HSV saturation or the Oklab chroma, or `None` to use the kind's default.
+    #[must_use]
+    pub fn new(kind: ColorSpaceKind, lightness: Option<f32>, chroma: Option<f32>) -> Self {
+        let lightness = lightness
+            .filter(|lightness| lightness.is_finite())
+            .unwrap_or_else(|| kind.default_lightness())
+            .clamp(0., 1.);
+        let chroma = chroma
+            .filter(|chroma| chroma.is_finite())
+            .unwrap_or_else(|| kind.default_chroma())
+            .clamp(0., 1.);
+
+        Self {
+            kind,
+            lightness,
+            chroma,
+        }
+    }
+
+    /// Returns the color space Hues are mapped through.
+    #[must_use]
+    pub const fn kind(self) -> ColorSpaceKind {
+        self.kind
+    }
+
+    /// Returns the HSL lightness, HSV value or Oklab lightness.
+    #[must_use]
+    pub const fn lightness(self) -> f32 {
+        self.lightness
+    }
+
+    /// Returns the HSL or HSV saturation or the Oklab chroma.
+    #[must_use]
+    pub const fn chroma(self) -> f32 {
+        self.chroma
+    }
+
+    /// Converts a Hue to its corresponding RGB value in the `ColorSpace`.
+    #[must_use]
+    pub fn to_rgb(self, hue: Hue) -> RGB {
+        let [r, g, b] = match self.kind {
+            ColorSpaceKind::Hsl => {
+                let chroma = (1. - 2_f32.mul_add(self.lightness, -1.).abs()) * self.chroma;
+                pure_hue(hue).map(|channel| chroma.mul_add(channel - 0.5, self.lightness))
+            }
+            ColorSpaceKind::Hsv => {
+                let chroma = self.lightness * self.chroma;
+                pure_hue(hue).map(|channel| chroma.mul_add(channel - 1., self.lightness))
+            }
+            ColorSpaceKind::Oklch => oklch_to_srgb(self.lightness, self.chroma, hue.get()),
+        };
+
+        RGB(to_byte(r), to_byte(g), to_byte(b))
+    }
+}
+
+/// Returns the fully saturated RGB channels of a Hue, each in [0, 1].
+///
+/// Derived from [`RapidTables` HSL to RGB color conversion](https://www.rapidtables.com/convert/color/hsl-to-rgb.html).
+fn pure_hue(hue: Hue) -> [f32; 3] {
+    let sextant = hue.get().rem_euclid(consts::PI * 2.) / consts::FRAC_PI_3;
+
+    [0., 4., 2.].map(|offset| (((sextant + offset) % 6. - 3.).abs() - 1.).clamp(0., 1.))
+}
+
+/// Converts an OKLCH color to gamma-encoded sRGB channels, each clipped to [0, 1].
+fn oklch_to_srgb(lightness: f32, chroma: f32, hue: f32) -> [f32; 3] {
+    oklab_to_linear_srgb([lightness, chroma * hue.cos(), chroma * hue.sin()])
+        .map(|channel| linear_to_srgb(channel.clamp(0., 1.)))
+}
+
+/// Multiplies a 3x3 matrix by a vector.
+fn transform(matrix: [[f32; 3]; 3], vector: [f32; 3]) -> [f32; 3] {
+    matrix.map(|row| row[2].mul_add(vector[2], row[1].mul_add(vector[1], row[0] * vector[0])))
+}
+
+/// The matrix converting an Oklab color to nonlinear LMS cone responses.
+pub const LAB_TO_LMS: [[f32; 3]; 3] = [
+    [1., 0.396_337_78, 0.215_803_76],
+    [1., -0.105_561_35, -0.063_854_17],
+    [1., -0.089_484_18, -1.291_485_5],
+];
+
+/// The matrix converting linear LMS cone responses to linear sRGB channels.
+pub const LMS_TO_RGB: [[f32; 3]; 3] = [
+    [4.076_741_7, -3.307_711_6, 0.230_969_94],
+    [-1.268_438, 2.609_757_4, -0.341_319_4],
+    [-0.004_196_086, -0.703_418_6, 1.707_614_7],
+];
+
+/// Converts an Oklab color to linear sRGB channels, which may lie outside of [0, 1].
+///
+/// Uses the matrices from [Björn Ottosson's Oklab reference](https://bottosson.github.io/posts/oklab/).
+pub fn oklab_to_linear_srgb(lab: [f32; 3]) -> [f32; 3] {
+    transform(
+        LMS_TO_RGB,
+        transform(LAB_TO_LMS, lab).map(|cone| cone.powi(3)),
+    )
+}
+
+/// Converts linear sRGB channels to an Oklab color.
+///
+/// Uses the matrices from [Björn Ottosson's Oklab reference](https://bottosson.github.io/posts/oklab/).
+pub fn linear_srgb_to_oklab(rgb: [f32; 3]) -> [f32; 3] {
+    const RGB_TO_LMS: [[f32; 3]; 3] = [
+        [0.412_221_46, 0.536_332_55, 0.051_445_995],
+        [0.211_903_5, 0.680_699_5, 0.107_396_96],
+        [0.088_302_46, 0.281_718_85, 0.629_978_7],
+    ];
+    const LMS_TO_LAB: [[f32; 3]; 3] = [
+        [0.210_454_26, 0.793_617_8, -0.004_072_047],
+        [1.977_998_5, -2.428_592_2, 0.450_593_7],
+        [0.025_904_037, 0.782_771_77, -0.808_675_77],
+    ];
+
+    transform(LMS_TO_LAB, transform(RGB_TO_LMS, rgb).map(f32::cbrt))
+}
+
+/// Applies the sRGB transfer function to a linear channel in [0, 1].
+pub fn linear_to_srgb(linear: f32) -> f32 {
+    if linear < 0.003_130_8 {
+        linear * 12.92
+    } else {
+        1.055_f32.mul_add(linear.powf(2.4_f32.recip()), -0.055)
+    }
+}
+
+/// Removes the sRGB transfer function from a gamma-encoded channel in [0, 1].
+pub fn srgb_to_linear(srgb: f32) -> f32 {
+    if srgb < 0.040_45 {
+        srgb / 12.92
+    } else {
+        ((srgb + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a channel in [0, 1] to a byte.
+fn to_byte(channel: f32) -> u8 {
+    (f32::from(u8::MAX) * channel) as u8
+}
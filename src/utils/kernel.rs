@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// The shape of the falloff used to weight each Source by its distance from a pixel.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KernelKind {
+    /// `1 / (distance^exponent + 1)`, where the parameter is the exponent - `2` gives the original inverse square weighting.
+    InversePower = 0,
+
+    /// `exp(-distance² / (2σ²))`, where the parameter is the standard deviation `σ` in pixels.
+    Gaussian = 1,
+
+    /// `exp(-distance / scale)`, where the parameter is the scale in pixels.
+    Exponential = 2,
+
+    /// `(1 - distance² / radius²)²` within the radius and zero beyond it, where the parameter is the radius in pixels.
+    SmoothCompact = 3,
+}
+
+impl KernelKind {
+    /// Returns the parameter used when none is specified.
+    #[must_use]
+    pub const fn default_parameter(self) -> f32 {
+        match self {
+            Self::InversePower => 2.,
+            Self::Gaussian => 100.,
+            Self::Exponential => 50.,
+            Self::SmoothCompact => 300.,
+        }
+    }
+
+    /// Returns the body of the GLSL function `float kernel_weight(float dist_sq)`, computing the same weight as `Kernel::weight`.
+    ///
+    /// The `Kernel`'s parameter is read from the `kernel_parameter` uniform, so it can change without recompiling the shader.
+    #[must_use]
+    pub const fn glsl(self) -> &'static str {
+        match self {
+            Self::InversePower => "return 1.0 / (pow(dist_sq, kernel_parameter * 0.5) + 1.0);",
+            Self::Gaussian => "return exp(-dist_sq / (2.0 * kernel_parameter * kernel_parameter));",
+            Self::Exponential => "return exp(-sqrt(dist_sq) / kernel_parameter);",
+            Self::SmoothCompact => {
+                "float falloff = max(1.0 - dist_sq / (kernel_parameter * kernel_parameter), 0.0);
+                return falloff * falloff;"
+            }
+        }
+    }
+}
+
+/// A distance-weighting kernel, shared by the CPU renderer and the generated `WebGL` shader.
+///
+/// Every kernel weighs a Source at distance zero as 1 and falls off towards 0 with distance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Kernel {
+    /// The shape of the falloff.
+    kind: KernelKind,
+
+    /// The exponent, standard deviation, scale or radius of the falloff, depending on its kind.
+    parameter: f32,
+}
+
+impl Default for Kernel {
+    /// Returns the original inverse square kernel.
+    fn default() -> Self {
+        Self::new(KernelKind::InversePower, None)
+    }
+}
+
+impl Kernel {
+    /// The smallest parameter accepted, to keep every kernel well defined.
+    const MIN_PARAMETER: f32 = 0.01;
+
+    /// Constructs a new Kernel.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - the shape of the falloff.
+    /// * `parameter` - the exponent, standard deviation, scale or radius of the falloff, or `None` to use the kind's default.
+    #[must_use]
+    pub fn new(kind: KernelKind, parameter: Option<f32>) -> Self {
+        let parameter = parameter
+            .filter(|parameter| parameter.is_finite())
+            .unwrap_or_else(|| kind.default_parameter())
+            .max(Self::MIN_PARAMETER);
+
+        Self { kind, parameter }
+    }
+
+    /// Returns the shape of the Kernel's falloff.
+    #[must_use]
+    pub const fn kind(self) -> KernelKind {
+        self.kind
+    }
+
+    /// Returns the exponent, standard deviation, scale or radius of the Kernel's falloff.
+    #[must_use]
+    pub const fn parameter(self) -> f32 {
+        self.parameter
+    }
+
+    /// Returns the weight of a Source at the given squared distance.
+    #[must_use]
+    pub fn weight(self, dist_sq: f32) -> f32 {
+        match self.kind {
+            KernelKind::InversePower => inverse_power(dist_sq, self.parameter / 2.),
+            KernelKind::Gaussian => gaussian(dist_sq, gaussian_scale(self.parameter)),
+            KernelKind::Exponential => exponential(dist_sq, self.parameter.recip()),
+            KernelKind::SmoothCompact => smooth_compact(dist_sq, self.parameter.powi(2).recip()),
+        }
+    }
+}
+
+/// `1 / (dist_sq^half_exponent + 1)`.
+#[inline]
+pub fn inverse_power(dist_sq: f32, half_exponent: f32) -> f32 {
+    1. / (dist_sq.powf(half_exponent) + 1.)
+}
+
+/// `1 / (dist_sq + 1)`, the inverse square kernel without the cost of `powf`.
+#[inline]
+pub fn inverse_square(dist_sq: f32) -> f32 {
+    1. / (dist_sq + 1.)
+}
+
+/// Returns the factor `scale` passed to `gaussian` for a standard deviation `sigma`.
+pub fn gaussian_scale(sigma: f32) -> f32 {
+    -(2. * sigma * sigma).recip()
+}
+
+/// `exp(dist_sq * scale)`, where `scale` is `-1 / (2σ²)`.
+#[inline]
+pub fn gaussian(dist_sq: f32, scale: f32) -> f32 {
+    (dist_sq * scale).exp()
+}
+
+/// `exp(-sqrt(dist_sq) * inverse_scale)`.
+#[inline]
+pub fn exponential(dist_sq: f32, inverse_scale: f32) -> f32 {
+    (-dist_sq.sqrt() * inverse_scale).exp()
+}
+
+/// `max(1 - dist_sq * inverse_radius_sq, 0)²`.
+#[inline]
+pub fn smooth_compact(dist_sq: f32, inverse_radius_sq: f32) -> f32 {
+    dist_sq.mul_add(-inverse_radius_sq, 1.).max(0.).powi(2)
+}
@@ -35,3 +35,41 @@ pub fn atan2_approx(cos: f32, sin: f32) -> f32 {
         -atan_approx(cos / sin) + consts::FRAC_PI_2
     }
 }
+
+/// Reflects a coordinate back into [0, `length`] as if it had bounced off of both ends, however far it overshoots.
+///
+/// Returns the reflected coordinate and whether it was reflected an odd number of times, reversing its direction.
+///
+/// # Parameters
+///
+/// * `position` - the coordinate.
+/// * `length` - the length of the range.
+pub fn reflect(position: f32, length: f32) -> (f32, bool) {
+    if length <= 0_f32 {
+        return (0_f32, false);
+    }
+
+    let period = length * 2_f32;
+    let period_position = position.rem_euclid(period);
+    let reversed = (position / length).floor().rem_euclid(2_f32) >= 1_f32;
+
+    if period_position > length {
+        (period - period_position, reversed)
+    } else {
+        (period_position, reversed)
+    }
+}
+
+/// Wraps a coordinate around into [0, `length`).
+///
+/// # Parameters
+///
+/// * `position` - the coordinate.
+/// * `length` - the length of the range.
+pub fn wrap(position: f32, length: f32) -> f32 {
+    if length <= 0_f32 {
+        0_f32
+    } else {
+        position.rem_euclid(length)
+    }
+}
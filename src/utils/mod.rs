@@ -1,3 +1,14 @@
 pub mod base;
+pub mod code;
+pub mod color;
+pub mod kernel;
 pub mod math;
+pub mod palette;
 pub mod panic;
+pub mod pointer;
+pub mod quadtree;
+pub mod render;
+pub mod scene;
+#[cfg(feature = "simd")]
+pub mod simd;
+pub mod upsample;
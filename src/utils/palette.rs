@@ -0,0 +1,244 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::f32::consts;
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+use crate::utils::base::{Hue, RGB};
+use crate::utils::color;
+
+const TWO_PI: f32 = consts::PI * 2_f32;
+
+/// The number of entries in a Palette's lookup table.
+pub const PALETTE_SIZE: usize = 256;
+
+/// The color space a Palette's stops are interpolated in.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PaletteSpace {
+    /// Gamma-encoded sRGB, as in CSS gradients.
+    Srgb = 0,
+
+    /// Linear sRGB, which keeps blends between saturated colors bright.
+    LinearRgb = 1,
+
+    /// Oklab, which keeps blends perceptually even.
+    Oklab = 2,
+}
+
+/// An error encountered while building a Palette.
+#[derive(Debug)]
+pub enum PaletteError {
+    /// The Palette has no colors.
+    Empty,
+
+    /// The number of positions does not match the number of colors.
+    PositionCount {
+        /// The number of colors.
+        colors: usize,
+
+        /// The number of positions.
+        positions: usize,
+    },
+
+    /// A position is not in [0, 1).
+    InvalidPosition(f32),
+}
+
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "a palette needs at least one color"),
+            Self::PositionCount { colors, positions } => write!(
+                f,
+                "a palette with {colors} colors needs {colors} positions, not {positions}"
+            ),
+            Self::InvalidPosition(position) => {
+                write!(f, "palette position {position} is not in [0, 1)")
+            }
+        }
+    }
+}
+
+impl Error for PaletteError {}
+
+/// A cyclic gradient which the blended Hue of each pixel indexes instead of the hue wheel.
+///
+/// The gradient is baked into a lookup table of `PALETTE_SIZE` colors, which the CPU renderer samples directly and the `WebGL` renderer uploads as a texture, so both produce the same colors.
+#[derive(Clone)]
+pub struct Palette {
+    /// The color of each stop, as `0xRRGGBB`.
+    colors: Vec<u32>,
+
+    /// The position of each stop in [0, 1), or `None` if the stops are evenly spaced.
+    positions: Option<Vec<f32>>,
+
+    /// The color space stops are interpolated in.
+    space: PaletteSpace,
+
+    /// The gradient's colors at evenly spaced positions, each offset by half an entry like texels in a texture.
+    table: [[u8; 3]; PALETTE_SIZE],
+}
+
+impl Palette {
+    /// The body of the GLSL function `vec3 hue_to_rgb(float hue)` when a Palette is used, sampling its lookup table from the `palette` texture.
+    pub const GLSL: &'static str = "return texture2D(palette, vec2(hue / TWO_PI, 0.5)).rgb;";
+
+    /// The equivalent of `GLSL` for GLSL ES 3.00 shaders, which sample textures with `texture` rather than `texture2D`.
+    pub const GLSL_ES_3: &'static str = "return texture(palette, vec2(hue / TWO_PI, 0.5)).rgb;";
+
+    /// Constructs a new Palette.
+    ///
+    /// The gradient wraps around, blending from the last stop back into the first.
+    ///
+    /// # Arguments
+    ///
+    /// * `colors` - the color of each stop, as `0xRRGGBB`.
+    /// * `positions` - the position of each stop in [0, 1), where 0 and 1 both correspond to a Hue of 0, or `None` to space the stops evenly.
+    /// * `space` - the color space stops are interpolated in.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are no colors, or the positions do not match the colors.
+    pub fn new(
+        colors: &[u32],
+        positions: Option<&[f32]>,
+        space: PaletteSpace,
+    ) -> Result<Self, PaletteError> {
+        if colors.is_empty() {
+            return Err(PaletteError::Empty);
+        }
+
+        let mut stops: Vec<(f32, [f32; 3])> = match positions {
+            Some(positions) if positions.len() != colors.len() => {
+                return Err(PaletteError::PositionCount {
+                    colors: colors.len(),
+                    positions: positions.len(),
+                });
+            }
+            Some(positions) => {
+                if let Some(&position) = positions
+                    .iter()
+                    .find(|position| !(0. ..1.).contains(*position))
+                {
+                    return Err(PaletteError::InvalidPosition(position));
+                }
+
+                positions
+                    .iter()
+                    .zip(colors)
+                    .map(|(&position, &color)| (position, to_space(color, space)))
+                    .collect()
+            }
+            None => colors
+                .iter()
+                .enumerate()
+                .map(|(i, &color)| (i as f32 / colors.len() as f32, to_space(color, space)))
+                .collect(),
+        };
+        stops.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        let table = std::array::from_fn(|i| {
+            let position = (i as f32 + 0.5) / PALETTE_SIZE as f32;
+
+            // The stops surrounding the position, wrapping around the end of the gradient.
+            let next = stops
+                .iter()
+                .position(|(stop, _)| *stop > position)
+                .unwrap_or(0);
+            let previous = next.checked_sub(1).unwrap_or(stops.len() - 1);
+            let (start, start_color) = stops[previous];
+            let (end, end_color) = stops[next];
+
+            let span = (end - start).rem_euclid(1.);
+            let t = if span > 0. {
+                (position - start).rem_euclid(1.) / span
+            } else {
+                0.
+            };
+
+            from_space(
+                [0, 1, 2].map(|channel| {
+                    (end_color[channel] - start_color[channel]).mul_add(t, start_color[channel])
+                }),
+                space,
+            )
+        });
+
+        Ok(Self {
+            colors: colors.to_vec(),
+            positions: positions.map(<[f32]>::to_vec),
+            space,
+            table,
+        })
+    }
+
+    /// Returns the color of each stop, as `0xRRGGBB`.
+    #[must_use]
+    pub fn colors(&self) -> &[u32] {
+        &self.colors
+    }
+
+    /// Returns the position of each stop in [0, 1), or `None` if the stops are evenly spaced.
+    #[must_use]
+    pub fn positions(&self) -> Option<&[f32]> {
+        self.positions.as_deref()
+    }
+
+    /// Returns the color space stops are interpolated in.
+    #[must_use]
+    pub const fn space(&self) -> PaletteSpace {
+        self.space
+    }
+
+    /// Returns the gradient's lookup table as tightly packed RGB bytes.
+    #[must_use]
+    pub const fn bytes(&self) -> &[u8] {
+        self.table.as_flattened()
+    }
+
+    /// Returns the color of the gradient at a Hue, linearly interpolating between the two nearest lookup table entries.
+    ///
+    /// Matches sampling the lookup table as a repeating texture with linear filtering.
+    #[must_use]
+    pub fn sample(&self, hue: Hue) -> RGB {
+        let texel = (hue.get() / TWO_PI).mul_add(PALETTE_SIZE as f32, -0.5);
+        let t = texel - texel.floor();
+
+        let index = texel.floor().rem_euclid(PALETTE_SIZE as f32) as usize;
+        let start = self.table[index];
+        let end = self.table[(index + 1) % PALETTE_SIZE];
+
+        let [r, g, b] = [0, 1, 2].map(|channel| {
+            (f32::from(end[channel]) - f32::from(start[channel]))
+                .mul_add(t, f32::from(start[channel]))
+                .round() as u8
+        });
+
+        RGB(r, g, b)
+    }
+}
+
+/// Converts a `0xRRGGBB` color into the color space its Palette is interpolated in.
+fn to_space(color: u32, space: PaletteSpace) -> [f32; 3] {
+    let srgb = [16, 8, 0].map(|shift| f32::from((color >> shift) as u8) / f32::from(u8::MAX));
+
+    match space {
+        PaletteSpace::Srgb => srgb,
+        PaletteSpace::LinearRgb => srgb.map(color::srgb_to_linear),
+        PaletteSpace::Oklab => color::linear_srgb_to_oklab(srgb.map(color::srgb_to_linear)),
+    }
+}
+
+/// Converts a color in the color space its Palette is interpolated in back into sRGB bytes.
+fn from_space(color: [f32; 3], space: PaletteSpace) -> [u8; 3] {
+    let srgb = match space {
+        PaletteSpace::Srgb => color,
+        PaletteSpace::LinearRgb => color.map(color::linear_to_srgb),
+        PaletteSpace::Oklab => color::oklab_to_linear_srgb(color)
+            .map(|channel| color::linear_to_srgb(channel.clamp(0., 1.))),
+    };
+
+    srgb.map(|channel| (f32::from(u8::MAX) * channel.clamp(0., 1.)).round() as u8)
+}
@@ -0,0 +1,231 @@
+use wasm_bindgen::prelude::*;
+
+use crate::utils::base::Source;
+
+/// The default distance, in pixels per frame, that an attracting or repelling pointer moves Sources directly beneath it.
+const DEFAULT_STRENGTH: f32 = 5.;
+
+/// The default distance, in pixels, beyond which an attracting or repelling pointer has no effect.
+const DEFAULT_RADIUS: f32 = 250.;
+
+/// The way a pointer interacts with the Sources of a Spectrum.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointerMode {
+    /// Grabs the nearest Source, which follows the pointer until it is released.
+    Grab = 0,
+
+    /// Pulls nearby Sources towards the pointer.
+    Attract = 1,
+
+    /// Pushes nearby Sources away from the pointer.
+    Repel = 2,
+}
+
+/// A pointer held down on the Spectrum canvas.
+#[derive(Clone)]
+struct Pointer {
+    /// The identifier distinguishing the pointer from other simultaneous pointers.
+    id: u32,
+
+    /// The x-coordinate of the pointer in the Spectrum canvas.
+    x: f32,
+
+    /// The y-coordinate of the pointer in the Spectrum canvas.
+    y: f32,
+
+    /// The way the pointer interacts with Sources.
+    mode: PointerMode,
+
+    /// The index of the Source grabbed by the pointer, if any.
+    grabbed: Option<usize>,
+}
+
+/// The pointers currently held down on a Spectrum canvas, such as a mouse or several touches.
+///
+/// Sources moved by the pointers may leave the canvas, and are moved back onto it by the `BaseSpectrum`.
+#[derive(Clone)]
+pub struct Pointers {
+    /// The pressed pointers, in the order they were pressed.
+    pressed: Vec<Pointer>,
+
+    /// The distance, in pixels per frame, that an attracting or repelling pointer moves Sources directly beneath it.
+    strength: f32,
+
+    /// The distance, in pixels, beyond which an attracting or repelling pointer has no effect.
+    radius: f32,
+}
+
+impl Default for Pointers {
+    fn default() -> Self {
+        Self {
+            pressed: Vec::new(),
+            strength: DEFAULT_STRENGTH,
+            radius: DEFAULT_RADIUS,
+        }
+    }
+}
+
+impl Pointers {
+    /// Presses a pointer, replacing any pointer with the same identifier.
+    ///
+    /// A grabbing pointer grabs the nearest Source which is not already grabbed, and moves it to the pointer.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the identifier of the pointer.
+    /// * `x` - the x-coordinate of the pointer in the Spectrum canvas.
+    /// * `y` - the y-coordinate of the pointer in the Spectrum canvas.
+    /// * `mode` - the way the pointer interacts with Sources.
+    /// * `sources` - the Spectrum's Sources.
+    pub fn down(&mut self, id: u32, x: f32, y: f32, mode: PointerMode, sources: &mut [Source]) {
+        self.up(id);
+
+        let grabbed = if mode == PointerMode::Grab {
+            let index = sources
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !self.is_grabbed(*index))
+                .map(|(index, source)| {
+                    let x_diff = source.x() - x;
+                    let y_diff = source.y() - y;
+                    (index, x_diff.mul_add(x_diff, y_diff * y_diff))
+                })
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(index, _)| index);
+
+            if let Some(index) = index {
+                sources[index].set_position(x, y);
+            }
+
+            index
+        } else {
+            None
+        };
+
+        self.pressed.push(Pointer {
+            id,
+            x,
+            y,
+            mode,
+            grabbed,
+        });
+    }
+
+    /// Moves a pressed pointer, along with any Source it has grabbed.
+    ///
+    /// Pointers which are not pressed are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the identifier of the pointer.
+    /// * `x` - the new x-coordinate of the pointer in the Spectrum canvas.
+    /// * `y` - the new y-coordinate of the pointer in the Spectrum canvas.
+    /// * `sources` - the Spectrum's Sources.
+    pub fn move_to(&mut self, id: u32, x: f32, y: f32, sources: &mut [Source]) {
+        if let Some(pointer) = self.pressed.iter_mut().find(|pointer| pointer.id == id) {
+            pointer.x = x;
+            pointer.y = y;
+
+            if let Some(source) = pointer.grabbed.and_then(|index| sources.get_mut(index)) {
+                source.set_position(x, y);
+            }
+        }
+    }
+
+    /// Releases a pointer, along with any Source it has grabbed.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the identifier of the pointer.
+    pub fn up(&mut self, id: u32) {
+        self.pressed.retain(|pointer| pointer.id != id);
+    }
+
+    /// Sets how strongly attracting and repelling pointers move Sources.
+    ///
+    /// # Arguments
+    ///
+    /// * `strength` - the distance, in pixels per frame, that Sources directly beneath a pointer are moved.
+    /// * `radius` - the distance, in pixels, beyond which a pointer has no effect.
+    pub const fn set_force(&mut self, strength: f32, radius: f32) {
+        self.strength = strength.max(0.);
+        self.radius = radius.max(0.);
+    }
+
+    /// Accounts for the removal of the Source at `index`, releasing it if it is grabbed.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - the index of the removed Source.
+    pub fn remove_source(&mut self, index: usize) {
+        for pointer in &mut self.pressed {
+            pointer.grabbed = match pointer.grabbed {
+                Some(grabbed) if grabbed == index => None,
+                Some(grabbed) if grabbed > index => Some(grabbed - 1),
+                grabbed => grabbed,
+            };
+        }
+    }
+
+    /// Applies the pointers to the Sources after they have moved by a number of frames.
+    ///
+    /// Grabbed Sources are held at their pointers, and the other Sources are pulled towards attracting pointers and pushed away from repelling pointers, falling off linearly with distance.
+    ///
+    /// # Arguments
+    ///
+    /// * `sources` - the Spectrum's Sources.
+    /// * `frames` - the number of frames the Sources have moved by.
+    /// * `unit_size` - the size of one coordinate unit, in the pixels the pointers' strength and radius are measured in.
+    pub fn tick(&self, sources: &mut [Source], frames: f32, unit_size: f32) {
+        let strength = self.strength / unit_size;
+        let radius = self.radius / unit_size;
+
+        for pointer in &self.pressed {
+            let attract = match pointer.mode {
+                PointerMode::Grab => {
+                    if let Some(source) = pointer.grabbed.and_then(|index| sources.get_mut(index)) {
+                        source.set_position(pointer.x, pointer.y);
+                    }
+                    continue;
+                }
+                PointerMode::Attract => true,
+                PointerMode::Repel => false,
+            };
+
+            for (index, source) in sources.iter_mut().enumerate() {
+                if self.is_grabbed(index) {
+                    continue;
+                }
+
+                let x_diff = pointer.x - source.x();
+                let y_diff = pointer.y - source.y();
+                let dist = x_diff.hypot(y_diff);
+
+                if dist > 0. && dist < radius {
+                    let falloff = strength * frames * (1. - dist / radius);
+                    // Attracted Sources stop at the pointer rather than overshooting it.
+                    let step = if attract { falloff.min(dist) } else { -falloff };
+
+                    source.set_position(
+                        (x_diff / dist).mul_add(step, source.x()),
+                        (y_diff / dist).mul_add(step, source.y()),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Returns whether no pointers are pressed.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.pressed.is_empty()
+    }
+
+    /// Returns whether the Source at `index` is grabbed by any pointer.
+    fn is_grabbed(&self, index: usize) -> bool {
+        self.pressed
+            .iter()
+            .any(|pointer| pointer.grabbed == Some(index))
+    }
+}
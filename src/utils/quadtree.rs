@@ -0,0 +1,326 @@
+use std::ops::Range;
+
+use crate::utils::render::SourceInfo;
+
+/// The largest number of Sources a leaf holds before it is split.
+const LEAF_SIZE: usize = 8;
+
+/// The deepest a `QuadTree` is split, which stops Sources at the same position from being split forever.
+const MAX_DEPTH: u32 = 16;
+
+/// A square region of a `QuadTree`, summarizing the Sources inside it.
+struct Node {
+    /// The x-coordinate of the mean position of the node's Sources.
+    x: f32,
+
+    /// The y-coordinate of the mean position of the node's Sources.
+    y: f32,
+
+    /// The side length of the node's square.
+    size: f32,
+
+    /// The number of Sources in the node.
+    count: f32,
+
+    /// The sum of the cosines of the node's Sources' Hues.
+    hue_cos: f32,
+
+    /// The sum of the sines of the node's Sources' Hues.
+    hue_sin: f32,
+
+    /// The indices of the node's children, which is empty if the node is a leaf.
+    children: Range<usize>,
+
+    /// The indices of the node's Sources in the `QuadTree`'s Sources.
+    sources: Range<usize>,
+}
+
+/// A Barnes-Hut quadtree over a Spectrum's Sources, which approximates groups of Sources far from a pixel as a single Source.
+///
+/// Each pixel visits roughly a logarithmic number of nodes rather than every Source, so scenes with thousands of Sources can be drawn interactively.
+pub struct QuadTree {
+    /// The nodes of the tree, starting with the root.
+    nodes: Vec<Node>,
+
+    /// The Sources, ordered so that each node's Sources are contiguous.
+    sources: Vec<SourceInfo>,
+}
+
+impl QuadTree {
+    /// Builds a new `QuadTree` over a set of Sources.
+    ///
+    /// # Arguments
+    ///
+    /// * `sources` - the position and Hue components of each Source, in reference pixels.
+    #[must_use]
+    pub fn new(sources: &[SourceInfo]) -> Self {
+        let mut sources = sources.to_vec();
+
+        let (min_x, min_y, max_x, max_y) = sources.iter().fold(
+            (
+                f32::INFINITY,
+                f32::INFINITY,
+                f32::NEG_INFINITY,
+                f32::NEG_INFINITY,
+            ),
+            |(min_x, min_y, max_x, max_y), &(x, y, _, _)| {
+                (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+            },
+        );
+        let size = (max_x - min_x).max(max_y - min_y).max(0.);
+
+        // The root is filled in once its children have been built.
+        let mut nodes = vec![leaf(&[], 0, 0.)];
+        nodes[0] = build(&mut nodes, &mut sources, 0, (min_x, min_y), size, 0);
+
+        Self { nodes, sources }
+    }
+
+    /// Sums the weights and weighted Hue components of every Source at a pixel, approximating nodes which are far enough away.
+    ///
+    /// Returns the summed weight and the summed cosine and sine of the Hues weighted by each Source's weight.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - the x-coordinate of the pixel, in reference pixels.
+    /// * `y` - the y-coordinate of the pixel, in reference pixels.
+    /// * `tolerance` - the largest ratio of a node's size to its distance from the pixel at which it is approximated.
+    /// * `weight` - returns the weight of a Source given its squared distance from the pixel.
+    pub fn accumulate<W: Fn(f32) -> f32>(
+        &self,
+        x: f32,
+        y: f32,
+        tolerance: f32,
+        weight: &W,
+    ) -> (f32, f32, f32) {
+        let mut sums = (0., 0., 0.);
+
+        if !self.sources.is_empty() {
+            self.accumulate_node(0, x, y, tolerance * tolerance, weight, &mut sums);
+        }
+
+        sums
+    }
+
+    /// Adds the contributions of a node's Sources at a pixel to `sums`.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - the index of the node.
+    /// * `x` - the x-coordinate of the pixel, in reference pixels.
+    /// * `y` - the y-coordinate of the pixel, in reference pixels.
+    /// * `tolerance_sq` - the square of the tolerance.
+    /// * `weight` - returns the weight of a Source given its squared distance from the pixel.
+    /// * `sums` - the summed weight and weighted Hue components so far.
+    fn accumulate_node<W: Fn(f32) -> f32>(
+        &self,
+        index: usize,
+        x: f32,
+        y: f32,
+        tolerance_sq: f32,
+        weight: &W,
+        sums: &mut (f32, f32, f32),
+    ) {
+        let node = &self.nodes[index];
+        let x_diff = x - node.x;
+        let y_diff = y - node.y;
+        let dist_sq = x_diff.mul_add(x_diff, y_diff * y_diff);
+        let size_sq = node.size * node.size;
+
+        if node.children.is_empty() {
+            for &(source_x, source_y, hue_cos, hue_sin) in &self.sources[node.sources.clone()] {
+                let x_diff = x - source_x;
+                let y_diff = y - source_y;
+
+                add(
+                    sums,
+                    weight(x_diff.mul_add(x_diff, y_diff * y_diff)),
+                    1.,
+                    hue_cos,
+                    hue_sin,
+                );
+            }
+        } else if size_sq < tolerance_sq * dist_sq {
+            add(
+                sums,
+                weight(dist_sq),
+                node.count,
+                node.hue_cos,
+                node.hue_sin,
+            );
+        } else {
+            for child in node.children.clone() {
+                self.accumulate_node(child, x, y, tolerance_sq, weight, sums);
+            }
+        }
+    }
+}
+
+/// Adds the contribution of one or more Sources with the same weight to `sums`.
+///
+/// # Arguments
+///
+/// * `sums` - the summed weight and weighted Hue components so far.
+/// * `weight` - the weight of each Source.
+/// * `count` - the number of Sources.
+/// * `hue_cos` - the summed cosines of the Sources' Hues.
+/// * `hue_sin` - the summed sines of the Sources' Hues.
+const fn add(sums: &mut (f32, f32, f32), weight: f32, count: f32, hue_cos: f32, hue_sin: f32) {
+    sums.0 = count.mul_add(weight, sums.0);
+    sums.1 = hue_cos.mul_add(weight, sums.1);
+    sums.2 = hue_sin.mul_add(weight, sums.2);
+}
+
+/// Returns a leaf node summarizing a set of Sources.
+///
+/// # Arguments
+///
+/// * `sources` - the node's Sources.
+/// * `offset` - the index of the first of the node's Sources in the `QuadTree`'s Sources.
+/// * `size` - the side length of the node's square.
+fn leaf(sources: &[SourceInfo], offset: usize, size: f32) -> Node {
+    let (x, y, hue_cos, hue_sin) = sources.iter().fold(
+        (0., 0., 0., 0.),
+        |(sum_x, sum_y, sum_cos, sum_sin), &(x, y, hue_cos, hue_sin)| {
+            (sum_x + x, sum_y + y, sum_cos + hue_cos, sum_sin + hue_sin)
+        },
+    );
+    let count = sources.len() as f32;
+
+    Node {
+        x: x / count.max(1.),
+        y: y / count.max(1.),
+        size,
+        count,
+        hue_cos,
+        hue_sin,
+        children: 0..0,
+        sources: offset..offset + sources.len(),
+    }
+}
+
+/// Builds the node covering a square, splitting it into quadrants if it holds too many Sources.
+///
+/// # Arguments
+///
+/// * `nodes` - the nodes built so far, which the node's descendants are added to.
+/// * `sources` - the node's Sources, which are reordered by quadrant.
+/// * `offset` - the index of the first of the node's Sources in the `QuadTree`'s Sources.
+/// * `origin` - the top left corner of the node's square.
+/// * `size` - the side length of the node's square.
+/// * `depth` - the depth of the node.
+fn build(
+    nodes: &mut Vec<Node>,
+    sources: &mut [SourceInfo],
+    offset: usize,
+    origin: (f32, f32),
+    size: f32,
+    depth: u32,
+) -> Node {
+    let mut node = leaf(sources, offset, size);
+
+    if sources.len() <= LEAF_SIZE || depth == MAX_DEPTH {
+        return node;
+    }
+
+    let half_size = size / 2.;
+    let (center_x, center_y) = (origin.0 + half_size, origin.1 + half_size);
+    let quadrant =
+        |&(x, y, _, _): &SourceInfo| usize::from(x >= center_x) + 2 * usize::from(y >= center_y);
+
+    sources.sort_unstable_by_key(quadrant);
+
+    let mut counts = [0; 4];
+    for source in sources.iter() {
+        counts[quadrant(source)] += 1;
+    }
+
+    let first_child = nodes.len();
+    let num_children = counts.iter().filter(|&&count| count > 0).count();
+    nodes.extend((0..num_children).map(|_| leaf(&[], 0, 0.)));
+    node.children = first_child..first_child + num_children;
+
+    let mut start = 0;
+    let mut child = first_child;
+    for (index, count) in counts.into_iter().enumerate() {
+        if count == 0 {
+            continue;
+        }
+
+        let child_origin = (
+            if index % 2 == 0 { origin.0 } else { center_x },
+            if index < 2 { origin.1 } else { center_y },
+        );
+        nodes[child] = build(
+            nodes,
+            &mut sources[start..start + count],
+            offset + start,
+            child_origin,
+            half_size,
+            depth + 1,
+        );
+
+        start += count;
+        child += 1;
+    }
+
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Returns 200 Sources scattered unevenly over a 100 pixel square, which the tree splits several times.
+    fn sources() -> Vec<SourceInfo> {
+        (0..200)
+            .map(|index| ((index * 37 % 101) as f32, (index * 53 % 103) as f32, 1., 0.))
+            .collect()
+    }
+
+    /// Sums the squared distances of the Sources from a pixel through a `QuadTree`, returning the sum and the number of Sources or nodes weighted.
+    fn accumulate_distances(tree: &QuadTree, x: f32, y: f32, tolerance: f32) -> (f32, usize) {
+        let calls = Cell::new(0);
+        let (sum, _, _) = tree.accumulate(x, y, tolerance, &|dist_sq| {
+            calls.set(calls.get() + 1);
+            dist_sq
+        });
+
+        (sum, calls.get())
+    }
+
+    #[test]
+    fn zero_tolerance_visits_every_source() {
+        let sources = sources();
+        let tree = QuadTree::new(&sources);
+        assert!(
+            tree.nodes.len() > 5,
+            "the tree was not split below its root's children"
+        );
+
+        for (x, y) in [(0., 0.), (50., 50.), (1000., -300.)] {
+            let (sum, calls) = accumulate_distances(&tree, x, y, 0.);
+            let expected: f32 = sources
+                .iter()
+                .map(|&(source_x, source_y, _, _)| {
+                    (x - source_x).mul_add(x - source_x, (y - source_y).powi(2))
+                })
+                .sum();
+
+            assert_eq!(calls, sources.len());
+            assert!((sum - expected).abs() <= expected * 1e-5);
+        }
+    }
+
+    #[test]
+    fn distant_groups_are_approximated() {
+        let tree = QuadTree::new(&sources());
+
+        // From far enough away, the whole tree is approximated by its root.
+        assert_eq!(accumulate_distances(&tree, 1000., -300., 0.5).1, 1);
+        // Nearby groups are split, but far ones are still approximated.
+        let (_, calls) = accumulate_distances(&tree, 0., 0., 0.5);
+        assert!(calls > 1 && calls < 200);
+    }
+}
@@ -0,0 +1,722 @@
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::utils::base::{BaseSpectrum, Hue, RGB, SOURCE_DROPOFF_FACTOR};
+use crate::utils::color::ColorSpace;
+use crate::utils::kernel::{self, Kernel, KernelKind};
+use crate::utils::math;
+use crate::utils::palette::Palette;
+use crate::utils::quadtree::QuadTree;
+#[cfg(feature = "simd")]
+use crate::utils::simd;
+use crate::utils::upsample::{self, MIN_RENDER_SCALE};
+
+/// The position and Hue components of a Source, in reference pixels.
+pub type SourceInfo = (f32, f32, f32, f32);
+
+/// Renders a `BaseSpectrum` into a buffer of RGBA pixel data on the CPU.
+///
+/// Holds no platform-specific state, so the same Spectrum can be drawn to a canvas, an image file or a test buffer.
+pub struct Renderer {
+    /// The rate at which the Spectrum fades out away from its Sources.
+    source_dropoff: u32,
+
+    /// The exponent applied to each pixel's summed Source weight to get its alpha value.
+    source_dropoff_exponent: f32,
+
+    /// The kernel weighting each Source by its distance from a pixel.
+    kernel: Kernel,
+
+    /// The color space each pixel's Hue is mapped through.
+    color_space: ColorSpace,
+
+    /// The gradient each pixel's Hue indexes instead of the color space, if any.
+    palette: Option<Palette>,
+
+    /// The largest ratio of a group of Sources' size to its distance from a pixel at which the group is approximated as a single Source, if any.
+    far_field_tolerance: Option<f32>,
+
+    /// The fraction of the canvas resolution the Spectrum is evaluated at before being upsampled to the canvas.
+    render_scale: f32,
+}
+
+impl Renderer {
+    /// Constructs a new Renderer.
+    ///
+    /// # Arguments
+    ///
+    /// * `source_dropoff` - the rate at which the Spectrum fades out away from its Sources.
+    #[must_use]
+    pub fn new(source_dropoff: u32) -> Self {
+        Self {
+            source_dropoff,
+            source_dropoff_exponent: get_source_dropoff(source_dropoff),
+            kernel: Kernel::default(),
+            color_space: ColorSpace::default(),
+            palette: None,
+            far_field_tolerance: None,
+            render_scale: 1.,
+        }
+    }
+
+    /// Returns the rate at which the Spectrum fades out away from its Sources.
+    #[must_use]
+    pub const fn source_dropoff(&self) -> u32 {
+        self.source_dropoff
+    }
+
+    /// Returns the exponent applied to each pixel's summed Source weight to get its alpha value.
+    #[must_use]
+    pub const fn source_dropoff_exponent(&self) -> f32 {
+        self.source_dropoff_exponent
+    }
+
+    pub fn update_source_dropoff(&mut self, source_dropoff: u32) {
+        self.source_dropoff = source_dropoff;
+        self.source_dropoff_exponent = get_source_dropoff(source_dropoff);
+    }
+
+    /// Returns the kernel weighting each Source by its distance from a pixel.
+    #[must_use]
+    pub const fn kernel(&self) -> Kernel {
+        self.kernel
+    }
+
+    pub const fn set_kernel(&mut self, kernel: Kernel) {
+        self.kernel = kernel;
+    }
+
+    /// Returns the color space each pixel's Hue is mapped through.
+    #[must_use]
+    pub const fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    pub const fn set_color_space(&mut self, color_space: ColorSpace) {
+        self.color_space = color_space;
+    }
+
+    /// Returns the gradient each pixel's Hue indexes instead of the color space, if any.
+    #[must_use]
+    pub const fn palette(&self) -> Option<&Palette> {
+        self.palette.as_ref()
+    }
+
+    pub fn set_palette(&mut self, palette: Option<Palette>) {
+        self.palette = palette;
+    }
+
+    /// Returns the largest ratio of a group of Sources' size to its distance from a pixel at which the group is approximated as a single Source, if any.
+    #[must_use]
+    pub const fn far_field_tolerance(&self) -> Option<f32> {
+        self.far_field_tolerance
+    }
+
+    /// Sets whether, and how aggressively, distant groups of Sources are approximated as single Sources.
+    ///
+    /// Drawing then visits a quadtree of the Sources rather than every Source for each pixel, which is much faster for scenes with thousands of Sources.
+    ///
+    /// # Arguments
+    ///
+    /// * `far_field_tolerance` - the largest ratio of a group's size to its distance from a pixel at which it is approximated, such as 0.5, or `None` to draw every Source exactly.
+    pub fn set_far_field_tolerance(&mut self, far_field_tolerance: Option<f32>) {
+        self.far_field_tolerance = far_field_tolerance.filter(|tolerance| *tolerance > 0.);
+    }
+
+    /// Returns the fraction of the canvas resolution the Spectrum is evaluated at before being upsampled to the canvas.
+    #[must_use]
+    pub const fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    /// Sets the fraction of the canvas resolution the Spectrum is evaluated at.
+    ///
+    /// As the Spectrum is smooth, evaluating it on a coarser grid and upsampling it is nearly lossless, while evaluating a quarter as many pixels at a scale of 0.5.
+    ///
+    /// # Arguments
+    ///
+    /// * `render_scale` - the fraction of the resolution, clamped to [`MIN_RENDER_SCALE`, 1].
+    pub const fn set_render_scale(&mut self, render_scale: f32) {
+        self.render_scale = render_scale.clamp(MIN_RENDER_SCALE, 1.);
+    }
+
+    /// Draws the `BaseSpectrum` into `data`, laid out row by row with four bytes (RGBA) per pixel.
+    ///
+    /// Assigns Hues to each pixel based off of an average distance weighting across all Sources, using the Renderer's `Kernel`, and maps them to colors through its `Palette` if it has one or its `ColorSpace` otherwise.
+    ///
+    /// As hue is a circular/periodic metric, a numerical average is inaccurate - instead, hue is broken into sine and cosine components which are summed and reconstructed into the resulting Hue.
+    ///
+    /// If the Renderer has a far-field tolerance, distant groups of Sources are approximated using a quadtree. Otherwise, with the `simd` feature, several pixels are drawn at once using SIMD instructions, and without it this is the same as `draw_scalar`.
+    ///
+    /// If the Renderer's render scale is below 1, the Spectrum is evaluated on a coarser grid which is then upsampled into `data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is smaller than `width * height * 4` bytes.
+    pub fn draw(&self, base: &BaseSpectrum, data: &mut [u8]) {
+        if self.render_scale < 1. {
+            let grid = Grid::new(base, self.render_scale);
+            let mut samples = vec![0; grid.width * grid.height * 4];
+            self.draw_grid(base, grid, &mut samples);
+
+            upsample::upsample(
+                &samples,
+                self.render_scale,
+                data,
+                base.width() as usize,
+                base.height() as usize,
+            );
+        } else {
+            self.draw_grid(base, Grid::new(base, 1.), data);
+        }
+    }
+
+    /// Draws the `BaseSpectrum` into `data`, evaluating it at each point of a grid.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - the `BaseSpectrum` to draw.
+    /// * `grid` - the grid of points to evaluate the `BaseSpectrum` at.
+    /// * `data` - the pixel data to draw into, one pixel per point.
+    fn draw_grid(&self, base: &BaseSpectrum, grid: Grid, data: &mut [u8]) {
+        #[cfg(feature = "simd")]
+        if self.far_field_tolerance.is_none() {
+            let sources = get_source_info(base);
+            draw_rows(grid, data, |y_float, pixel_size, row| {
+                simd::draw_row(self, &sources, y_float, pixel_size, row);
+            });
+
+            return;
+        }
+
+        self.draw_kernel(base, grid, data, self.far_field_tolerance);
+    }
+
+    /// Draws the `BaseSpectrum` into `data` one pixel and Source at a time, like `draw` without the `simd` feature, a far-field tolerance or a reduced render scale.
+    ///
+    /// Kept as the reference the SIMD and far-field renderers are checked against.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is smaller than `width * height * 4` bytes.
+    pub fn draw_scalar(&self, base: &BaseSpectrum, data: &mut [u8]) {
+        self.draw_kernel(base, Grid::new(base, 1.), data, None);
+    }
+
+    /// Draws the `BaseSpectrum` into `data` one pixel at a time, using the Renderer's `Kernel`.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - the `BaseSpectrum` to draw.
+    /// * `grid` - the grid of points to evaluate the `BaseSpectrum` at.
+    /// * `data` - the pixel data to draw into, one pixel per point.
+    /// * `far_field_tolerance` - the far-field tolerance to approximate distant Sources with, or `None` to draw every Source exactly.
+    fn draw_kernel(
+        &self,
+        base: &BaseSpectrum,
+        grid: Grid,
+        data: &mut [u8],
+        far_field_tolerance: Option<f32>,
+    ) {
+        let parameter = self.kernel.parameter();
+
+        // Dispatch once per frame rather than once per pixel and Source.
+        match self.kernel.kind() {
+            KernelKind::InversePower if (parameter - 2.).abs() < f32::EPSILON => {
+                self.draw_with(
+                    base,
+                    grid,
+                    data,
+                    far_field_tolerance,
+                    kernel::inverse_square,
+                );
+            }
+            KernelKind::InversePower => {
+                let half_exponent = parameter / 2.;
+                self.draw_with(base, grid, data, far_field_tolerance, |dist_sq| {
+                    kernel::inverse_power(dist_sq, half_exponent)
+                });
+            }
+            KernelKind::Gaussian => {
+                let scale = kernel::gaussian_scale(parameter);
+                self.draw_with(base, grid, data, far_field_tolerance, |dist_sq| {
+                    kernel::gaussian(dist_sq, scale)
+                });
+            }
+            KernelKind::Exponential => {
+                let inverse_scale = parameter.recip();
+                self.draw_with(base, grid, data, far_field_tolerance, |dist_sq| {
+                    kernel::exponential(dist_sq, inverse_scale)
+                });
+            }
+            KernelKind::SmoothCompact => {
+                let inverse_radius_sq = parameter.powi(2).recip();
+                self.draw_with(base, grid, data, far_field_tolerance, |dist_sq| {
+                    kernel::smooth_compact(dist_sq, inverse_radius_sq)
+                });
+            }
+        }
+    }
+
+    /// Draws the `BaseSpectrum` into `data` one pixel at a time, weighting each Source with `weight`.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - the `BaseSpectrum` to draw.
+    /// * `grid` - the grid of points to evaluate the `BaseSpectrum` at.
+    /// * `data` - the pixel data to draw into, one pixel per point.
+    /// * `far_field_tolerance` - the far-field tolerance to approximate distant Sources with, or `None` to draw every Source exactly.
+    /// * `weight` - returns the weight of a Source given its squared distance from a pixel.
+    fn draw_with<W: Fn(f32) -> f32 + Sync>(
+        &self,
+        base: &BaseSpectrum,
+        grid: Grid,
+        data: &mut [u8],
+        far_field_tolerance: Option<f32>,
+        weight: W,
+    ) {
+        let sources = get_source_info(base);
+
+        if let Some(tolerance) = far_field_tolerance {
+            let tree = QuadTree::new(&sources);
+
+            draw_rows(grid, data, |y_float, pixel_size, row| {
+                for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+                    let (dist_factor_inverse_sum, hue_vector_cos, hue_vector_sin) =
+                        tree.accumulate(x as f32 * pixel_size, y_float, tolerance, &weight);
+
+                    pixel.copy_from_slice(&self.shade(
+                        dist_factor_inverse_sum,
+                        hue_vector_cos,
+                        hue_vector_sin,
+                    ));
+                }
+            });
+        } else {
+            draw_rows(grid, data, |y_float, pixel_size, row| {
+                self.draw_row(&sources, y_float, pixel_size, row, &weight);
+            });
+        }
+    }
+
+    /// Draws a single row of pixels.
+    ///
+    /// # Arguments
+    ///
+    /// * `sources` - the position and Hue components of each Source, in reference pixels.
+    /// * `y_float` - the y-coordinate of the row, in reference pixels.
+    /// * `pixel_size` - the size of a pixel, in reference pixels.
+    /// * `row` - the row's pixel data.
+    /// * `weight` - returns the weight of a Source given its squared distance from a pixel.
+    fn draw_row<W: Fn(f32) -> f32>(
+        &self,
+        sources: &[SourceInfo],
+        y_float: f32,
+        pixel_size: f32,
+        row: &mut [u8],
+        weight: &W,
+    ) {
+        for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+            let x_float = x as f32 * pixel_size;
+            let mut dist_factor_inverse_sum: f32 = 0.;
+
+            let (hue_vector_cos, hue_vector_sin) = sources.iter().fold(
+                (0., 0.),
+                |(sum_cos, sum_sin), &(source_x, source_y, hue_cos, hue_sin)| {
+                    let x_diff = x_float - source_x;
+                    let y_diff = y_float - source_y;
+
+                    let dist_factor_inverse = weight(x_diff.mul_add(x_diff, y_diff * y_diff));
+                    dist_factor_inverse_sum += dist_factor_inverse;
+
+                    (
+                        hue_cos.mul_add(dist_factor_inverse, sum_cos),
+                        hue_sin.mul_add(dist_factor_inverse, sum_sin),
+                    )
+                },
+            );
+
+            pixel.copy_from_slice(&self.shade(
+                dist_factor_inverse_sum,
+                hue_vector_cos,
+                hue_vector_sin,
+            ));
+        }
+    }
+
+    /// Returns the RGBA color of a pixel from the summed weights and weighted Hue components of the Sources.
+    ///
+    /// # Arguments
+    ///
+    /// * `dist_factor_inverse_sum` - the summed weight of every Source.
+    /// * `hue_vector_cos` - the summed cosines of the Sources' Hues, weighted by each Source's weight.
+    /// * `hue_vector_sin` - the summed sines of the Sources' Hues, weighted by each Source's weight.
+    fn shade(
+        &self,
+        dist_factor_inverse_sum: f32,
+        hue_vector_cos: f32,
+        hue_vector_sin: f32,
+    ) -> [u8; 4] {
+        let adjusted_dist_factor_inverse_sum = dist_factor_inverse_sum
+            .min(1.)
+            .powf(self.source_dropoff_exponent);
+        let alpha = (f32::from(u8::MAX) * adjusted_dist_factor_inverse_sum) as u8;
+
+        let hue = Hue::new(math::atan2_approx(hue_vector_cos, hue_vector_sin));
+        let RGB(r, g, b) = self.palette.as_ref().map_or_else(
+            || self.color_space.to_rgb(hue),
+            |palette| palette.sample(hue),
+        );
+
+        [r, g, b, alpha]
+    }
+}
+
+/// The points a `BaseSpectrum` is evaluated at, which are coarser than its pixels when rendering at a reduced scale.
+#[derive(Clone, Copy)]
+struct Grid {
+    /// The number of points in each row.
+    width: usize,
+
+    /// The number of rows.
+    height: usize,
+
+    /// The distance between neighboring points, in reference pixels.
+    pixel_size: f32,
+}
+
+impl Grid {
+    /// Returns the grid evaluating a `BaseSpectrum` at a fraction of its resolution, spanning every pixel.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - the `BaseSpectrum` to evaluate.
+    /// * `render_scale` - the fraction of the resolution.
+    fn new(base: &BaseSpectrum, render_scale: f32) -> Self {
+        Self {
+            width: upsample::scaled_len(base.width() as usize, render_scale),
+            height: upsample::scaled_len(base.height() as usize, render_scale),
+            // Distances are measured in pixels of the reference canvas, which is the canvas itself unless coordinates are normalized.
+            pixel_size: base.pixel_size() / render_scale,
+        }
+    }
+}
+
+/// Draws a grid of points into `data` row by row.
+///
+/// With the `parallel` feature, rows are split across threads.
+///
+/// # Arguments
+///
+/// * `grid` - the grid of points to draw.
+/// * `data` - the pixel data to draw into, one pixel per point.
+/// * `draw_row` - draws a row given its y-coordinate and the distance between points, both in reference pixels, and the row's pixel data.
+fn draw_rows<R>(grid: Grid, data: &mut [u8], draw_row: R)
+where
+    R: Fn(f32, f32, &mut [u8]) + Sync,
+{
+    let Grid {
+        width,
+        height,
+        pixel_size,
+    } = grid;
+    assert!(
+        data.len() >= width * height * 4,
+        "pixel buffer is too small for a {width}x{height} Spectrum"
+    );
+
+    let draw_row = |(y, row): (usize, &mut [u8])| {
+        draw_row(y as f32 * pixel_size, pixel_size, row);
+    };
+
+    #[cfg(feature = "parallel")]
+    data.par_chunks_exact_mut(width * 4)
+        .take(height)
+        .enumerate()
+        .for_each(draw_row);
+
+    #[cfg(not(feature = "parallel"))]
+    data.chunks_exact_mut(width * 4)
+        .take(height)
+        .enumerate()
+        .for_each(draw_row);
+}
+
+/// Returns the position and Hue components of each of the `BaseSpectrum`'s Sources, in reference pixels.
+fn get_source_info(base: &BaseSpectrum) -> Vec<SourceInfo> {
+    let unit_size = base.unit_size();
+
+    base.sources()
+        .iter()
+        .map(|source| {
+            (
+                source.x() * unit_size,
+                source.y() * unit_size,
+                source.hue_cos(),
+                source.hue_sin(),
+            )
+        })
+        .collect()
+}
+
+/// Converts a source dropoff input into the exponent used when rendering.
+pub fn get_source_dropoff(source_dropoff: u32) -> f32 {
+    ((source_dropoff as f32) * SOURCE_DROPOFF_FACTOR).powi(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "simd")]
+    use crate::utils::color::ColorSpaceKind;
+    #[cfg(feature = "simd")]
+    use crate::utils::palette::PaletteSpace;
+
+    const KERNEL_KINDS: [KernelKind; 4] = [
+        KernelKind::InversePower,
+        KernelKind::Gaussian,
+        KernelKind::Exponential,
+        KernelKind::SmoothCompact,
+    ];
+
+    /// Returns the largest difference between any channel of any pixel of two drawings.
+    fn max_difference(data: &[u8], expected: &[u8]) -> u8 {
+        data.iter()
+            .zip(expected)
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the mean difference between the channels of two drawings.
+    fn mean_difference(data: &[u8], expected: &[u8]) -> f32 {
+        let total: u32 = data
+            .iter()
+            .zip(expected)
+            .map(|(a, b)| u32::from(a.abs_diff(*b)))
+            .sum();
+
+        total as f32 / data.len() as f32
+    }
+
+    /// Draws a `BaseSpectrum` with `draw` and with `draw_scalar`, returning the largest difference between any channel of any pixel.
+    #[cfg(feature = "simd")]
+    fn max_scalar_difference(renderer: &Renderer, base: &BaseSpectrum) -> u8 {
+        let len = base.width() as usize * base.height() as usize * 4;
+        let mut data = vec![0; len];
+        let mut expected = vec![0; len];
+        renderer.draw(base, &mut data);
+        renderer.draw_scalar(base, &mut expected);
+
+        max_difference(&data, &expected)
+    }
+
+    /// Returns a `BaseSpectrum` with still Sources at the given positions.
+    ///
+    /// Their Hues span a single radian, so the weighted sum of their Hue components never nearly cancels out, which would leave the drawn Hue at the mercy of rounding.
+    fn with_sources(width: u32, height: u32, positions: &[(f32, f32)]) -> BaseSpectrum {
+        let mut base = BaseSpectrum::new(width, height, positions.len() as u32, 40, 30, Some(3));
+
+        for (index, &(x, y)) in positions.iter().enumerate() {
+            let hue = (index % 10) as f32 / 10.;
+            let source = base.create_source(x, y, hue, 0., 0., 0.);
+            base.set_source(index, source).unwrap();
+        }
+
+        base
+    }
+
+    /// Returns the positions of 300 Sources scattered unevenly over a 96 by 54 canvas, which the quadtree splits several times.
+    fn scattered() -> Vec<(f32, f32)> {
+        (0..300)
+            .map(|index| ((index * 37 % 97) as f32, (index * 23 % 55) as f32))
+            .collect()
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn simd_matches_scalar() {
+        // The vectorized `exp` and `powf` are approximations, so channels may be off by a little rounding.
+        const MAX_DIFFERENCE: u8 = 3;
+
+        let palette = Palette::new(
+            &[0xff_00_00, 0x00_ff_00, 0x00_00_ff],
+            None,
+            PaletteSpace::Oklab,
+        )
+        .unwrap();
+        let kernels = KERNEL_KINDS
+            .map(|kind| Kernel::new(kind, None))
+            .into_iter()
+            .chain([Kernel::new(KernelKind::InversePower, Some(3.))]);
+
+        for kernel in kernels {
+            let mut renderer = Renderer::new(50);
+            renderer.set_kernel(kernel);
+
+            // Rows which are not a multiple of the SIMD width end with a partial batch of pixels.
+            for width in [1, 7, 8, 13, 37] {
+                for normalized in [false, true] {
+                    let mut base = BaseSpectrum::new(width, 11, 9, 40, 30, Some(u64::from(width)));
+                    base.set_normalized(normalized);
+
+                    for color_space in [
+                        ColorSpaceKind::Hsl,
+                        ColorSpaceKind::Hsv,
+                        ColorSpaceKind::Oklch,
+                    ] {
+                        renderer.set_palette(None);
+                        renderer.set_color_space(ColorSpace::new(color_space, None, None));
+                        let difference = max_scalar_difference(&renderer, &base);
+                        assert!(
+                            difference <= MAX_DIFFERENCE,
+                            "{kernel:?} {color_space:?} at width {width}, normalized {normalized}: off by {difference}"
+                        );
+                    }
+
+                    renderer.set_palette(Some(palette.clone()));
+                    let difference = max_scalar_difference(&renderer, &base);
+                    assert!(
+                        difference <= MAX_DIFFERENCE,
+                        "{kernel:?} palette at width {width}, normalized {normalized}: off by {difference}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn zero_far_field_tolerance_matches_scalar() {
+        // No group of Sources is small enough to approximate, so every leaf of the quadtree is visited.
+        let base = with_sources(96, 54, &scattered());
+        let len = 96 * 54 * 4;
+        let mut data = vec![0; len];
+        let mut expected = vec![0; len];
+
+        for kind in KERNEL_KINDS {
+            let mut renderer = Renderer::new(50);
+            renderer.set_kernel(Kernel::new(kind, None));
+            renderer.draw_kernel(&base, Grid::new(&base, 1.), &mut data, Some(0.));
+            renderer.draw_scalar(&base, &mut expected);
+
+            assert_eq!(data, expected, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn far_field_matches_scalar() {
+        // Approximating a group of Sources by its mean position cancels the error to first order, so it grows with the square of the tolerance, most of all for the steep inverse square kernel.
+        const MAX_DIFFERENCE_PER_TOLERANCE_SQ: f32 = 50.;
+        const MEAN_DIFFERENCE_PER_TOLERANCE_SQ: f32 = 1.;
+
+        let (width, height) = (96, 54);
+        let edges: Vec<_> = (0..40)
+            .map(|index| {
+                let along = index as f32 * 10.;
+                match index % 4 {
+                    0 => (along.min(96.), 0.),
+                    1 => (along.min(96.), 54.),
+                    2 => (0., along.min(54.)),
+                    _ => (96., along.min(54.)),
+                }
+            })
+            .collect();
+        let scenes = [
+            ("scattered", scattered()),
+            ("single", vec![(30., 20.)]),
+            // More coincident Sources than fit in a leaf, which can never be split apart.
+            ("coincident", vec![(40., 25.); 40]),
+            ("edges", edges),
+        ];
+
+        for (name, positions) in scenes {
+            let base = with_sources(width, height, &positions);
+            let len = width as usize * height as usize * 4;
+            let mut expected = vec![0; len];
+            let mut data = vec![0; len];
+
+            for tolerance in [0.25, 0.5] {
+                let tolerance_sq = tolerance * tolerance;
+                let max_allowed = (MAX_DIFFERENCE_PER_TOLERANCE_SQ * tolerance_sq).ceil() as u8;
+
+                for kind in KERNEL_KINDS {
+                    let mut renderer = Renderer::new(50);
+                    renderer.set_kernel(Kernel::new(kind, None));
+                    renderer.set_far_field_tolerance(Some(tolerance));
+                    renderer.draw_scalar(&base, &mut expected);
+                    renderer.draw(&base, &mut data);
+
+                    let difference = max_difference(&data, &expected);
+                    assert!(
+                        difference <= max_allowed,
+                        "{kind:?} with {name} Sources at {tolerance}: off by {difference}"
+                    );
+                    let mean = mean_difference(&data, &expected);
+                    assert!(
+                        mean <= MEAN_DIFFERENCE_PER_TOLERANCE_SQ * tolerance_sq,
+                        "{kind:?} with {name} Sources at {tolerance}: off by {mean} on average"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reduced_render_scale_matches_full_resolution() {
+        const MEAN_DIFFERENCE: f32 = 0.25;
+
+        let base = BaseSpectrum::new(37, 21, 12, 40, 30, Some(9));
+        let len = 37 * 21 * 4;
+        let mut data = vec![0; len];
+        let mut expected = vec![0; len];
+
+        for kind in KERNEL_KINDS {
+            let mut renderer = Renderer::new(50);
+            renderer.set_kernel(Kernel::new(kind, None));
+            renderer.draw(&base, &mut expected);
+
+            for (render_scale, step) in [(0.5, 2), (MIN_RENDER_SCALE, 4)] {
+                renderer.set_render_scale(render_scale);
+                renderer.draw(&base, &mut data);
+                renderer.set_render_scale(1.);
+
+                // Pixels on samples are evaluated at the same point as at full resolution.
+                for y in (0..21).step_by(step) {
+                    for x in (0..37).step_by(step) {
+                        let pixel = (y * 37 + x) * 4;
+                        assert_eq!(
+                            data[pixel..pixel + 4],
+                            expected[pixel..pixel + 4],
+                            "{kind:?} at {render_scale}, pixel ({x}, {y})"
+                        );
+                    }
+                }
+
+                // The inverse square kernel peaks too sharply at each Source for any interpolation between samples to follow.
+                if kind != KernelKind::InversePower {
+                    let mean = mean_difference(&data, &expected);
+                    assert!(
+                        mean <= MEAN_DIFFERENCE,
+                        "{kind:?} at {render_scale}: off by {mean} on average"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reduced_render_scale_fills_every_pixel() {
+        let base = BaseSpectrum::new(37, 21, 12, 40, 30, Some(9));
+        let mut renderer = Renderer::new(0);
+
+        for render_scale in [MIN_RENDER_SCALE, 0.3, 0.7] {
+            renderer.set_render_scale(render_scale);
+
+            // A source dropoff of 0 makes every drawn pixel opaque, so any pixel left transparent was never written.
+            let mut data = vec![0; 37 * 21 * 4];
+            renderer.draw(&base, &mut data);
+
+            assert!(data.chunks_exact(4).all(|pixel| pixel[3] == u8::MAX));
+        }
+    }
+}
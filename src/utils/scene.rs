@@ -0,0 +1,499 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+
+use crate::utils::base::{self, BaseSpectrum, BoundaryMode, Source};
+use crate::utils::color::{ColorSpace, ColorSpaceKind};
+use crate::utils::kernel::{Kernel, KernelKind};
+use crate::utils::palette::{Palette, PaletteError, PaletteSpace};
+use crate::utils::render::Renderer;
+
+/// The current version of the scene format.
+pub const SCENE_VERSION: u32 = 2;
+
+/// The largest number of pixels, those of an 8K UHD canvas, in a scene which can be restored.
+pub const MAX_SCENE_PIXELS: u32 = 7_680 * 4_320;
+
+/// The oldest version of the scene format which can still be read, before rendering settings were saved.
+const SCENE_VERSION_SOURCES: u32 = 1;
+
+/// A snapshot of a Spectrum which can be saved and restored exactly.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scene {
+    /// The version of the scene format.
+    pub version: u32,
+
+    /// The width of the Spectrum canvas.
+    pub width: u32,
+
+    /// The height of the Spectrum canvas.
+    pub height: u32,
+
+    /// The range of each Source's movement speed (`dx`, `dy`).
+    pub movement_speed: u32,
+
+    /// The range of each Source's color speed (`dh`).
+    pub color_speed: u32,
+
+    /// The rate at which the Spectrum fades out away from its Sources.
+    pub source_dropoff: u32,
+
+    /// The seed the Spectrum's Sources were originally generated from.
+    pub seed: u64,
+
+    /// Whether the Sources' coordinates are fractions of the canvas diagonal rather than pixels.
+    #[serde(default)]
+    pub normalized: bool,
+
+    /// The kernel weighting each Source by its distance from a pixel.
+    #[serde(default)]
+    pub kernel: SceneKernel,
+
+    /// The color space each pixel's Hue is mapped through.
+    #[serde(default)]
+    pub color_space: SceneColorSpace,
+
+    /// The gradient each pixel's Hue indexes instead of the color space, if any.
+    #[serde(default)]
+    pub palette: Option<ScenePalette>,
+
+    /// The way Sources behave when they reach the edge of the canvas.
+    #[serde(default)]
+    pub boundary_mode: BoundaryMode,
+
+    /// The number of frames after which the Spectrum returns to its current state, if it loops.
+    #[serde(default)]
+    pub loop_period: Option<u32>,
+
+    /// The fraction of the canvas resolution the Spectrum is evaluated at.
+    #[serde(default = "default_render_scale")]
+    pub render_scale: f32,
+
+    /// The largest ratio of a group of Sources' size to its distance from a pixel at which the group is approximated as a single Source, if any.
+    #[serde(default)]
+    pub far_field_tolerance: Option<f32>,
+
+    /// The state of each of the Spectrum's Sources.
+    pub sources: Vec<SceneSource>,
+}
+
+/// A snapshot of a Spectrum's kernel.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneKernel {
+    /// The shape of the falloff.
+    pub kind: KernelKind,
+
+    /// The exponent, standard deviation, scale or radius of the falloff, depending on its kind.
+    pub parameter: f32,
+}
+
+impl Default for SceneKernel {
+    /// Returns the original inverse square kernel, which scenes without a kernel were drawn with.
+    fn default() -> Self {
+        Self::from(Kernel::default())
+    }
+}
+
+impl From<Kernel> for SceneKernel {
+    fn from(kernel: Kernel) -> Self {
+        Self {
+            kind: kernel.kind(),
+            parameter: kernel.parameter(),
+        }
+    }
+}
+
+/// A snapshot of a Spectrum's color space.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneColorSpace {
+    /// The color space Hues are mapped through.
+    pub kind: ColorSpaceKind,
+
+    /// The HSL lightness, HSV value or Oklab lightness.
+    pub lightness: f32,
+
+    /// The HSL or HSV saturation or the Oklab chroma.
+    pub chroma: f32,
+}
+
+impl Default for SceneColorSpace {
+    /// Returns the original fully saturated HSL color space, which scenes without a color space were drawn with.
+    fn default() -> Self {
+        Self::from(ColorSpace::default())
+    }
+}
+
+impl From<ColorSpace> for SceneColorSpace {
+    fn from(color_space: ColorSpace) -> Self {
+        Self {
+            kind: color_space.kind(),
+            lightness: color_space.lightness(),
+            chroma: color_space.chroma(),
+        }
+    }
+}
+
+/// A snapshot of a Spectrum's palette, storing its stops rather than its lookup table.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenePalette {
+    /// The color of each stop, as `0xRRGGBB`.
+    pub colors: Vec<u32>,
+
+    /// The position of each stop in [0, 1), or `None` if the stops are evenly spaced.
+    pub positions: Option<Vec<f32>>,
+
+    /// The color space stops are interpolated in.
+    pub space: PaletteSpace,
+}
+
+impl ScenePalette {
+    /// Rebuilds the Palette captured by the `ScenePalette`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stops do not form a valid Palette.
+    pub fn to_palette(&self) -> Result<Palette, PaletteError> {
+        Palette::new(&self.colors, self.positions.as_deref(), self.space)
+    }
+}
+
+impl From<&Palette> for ScenePalette {
+    fn from(palette: &Palette) -> Self {
+        Self {
+            colors: palette.colors().to_vec(),
+            positions: palette.positions().map(<[f32]>::to_vec),
+            space: palette.space(),
+        }
+    }
+}
+
+/// Returns the render scale of scenes which were saved without one, which were evaluated at full resolution.
+const fn default_render_scale() -> f32 {
+    1.
+}
+
+/// A snapshot of a single Source.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneSource {
+    /// The x-coordinate of the Source in the Spectrum canvas.
+    pub x: f32,
+
+    /// The y-coordinate of the Source in the Spectrum canvas.
+    pub y: f32,
+
+    /// The Hue value of the Source, in radians.
+    pub hue: f32,
+
+    /// The rate of movement in the x direction.
+    pub dx: f32,
+
+    /// The rate of movement in the y direction.
+    pub dy: f32,
+
+    /// The rate of change in the Source's Hue.
+    pub dh: f32,
+
+    /// The random factor scaling the Source's movement speed in the x direction.
+    pub dx_random: f32,
+
+    /// The random factor scaling the Source's movement speed in the y direction.
+    pub dy_random: f32,
+
+    /// The random factor scaling the Source's color speed.
+    pub dh_random: f32,
+}
+
+/// Only used to read the version of a scene before parsing the rest of it.
+#[derive(Deserialize)]
+struct SceneVersion {
+    version: u32,
+}
+
+/// An error encountered while reading a scene.
+#[derive(Debug)]
+pub enum SceneError {
+    /// The scene is not valid JSON or is missing fields.
+    Json(serde_json::Error),
+
+    /// The scene was written in a format version this build cannot read.
+    UnsupportedVersion(u32),
+
+    /// The scene code is malformed.
+    InvalidCode(&'static str),
+
+    /// The scene's palette is not a valid Palette.
+    InvalidPalette(PaletteError),
+
+    /// The scene's canvas is empty or too large to allocate.
+    InvalidSize {
+        /// The width of the canvas.
+        width: u32,
+
+        /// The height of the canvas.
+        height: u32,
+    },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "invalid scene: {err}"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported scene version {version} (expected {SCENE_VERSION_SOURCES} to {SCENE_VERSION})"
+            ),
+            Self::InvalidCode(reason) => write!(f, "invalid scene code: {reason}"),
+            Self::InvalidPalette(err) => write!(f, "invalid scene palette: {err}"),
+            Self::InvalidSize { width, height } => write!(
+                f,
+                "invalid scene size {width}x{height} (expected 1 to {MAX_SCENE_PIXELS} pixels)"
+            ),
+        }
+    }
+}
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Json(err) => Some(err),
+            Self::InvalidPalette(err) => Some(err),
+            Self::UnsupportedVersion(_) | Self::InvalidCode(_) | Self::InvalidSize { .. } => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for SceneError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+impl Scene {
+    /// Captures the current state of a Spectrum.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - the Spectrum's `BaseSpectrum`.
+    /// * `renderer` - the Spectrum's `Renderer`.
+    #[must_use]
+    pub fn capture(base: &BaseSpectrum, renderer: &Renderer) -> Self {
+        Self {
+            version: SCENE_VERSION,
+            width: base.width(),
+            height: base.height(),
+            movement_speed: base.movement_speed(),
+            color_speed: base.color_speed(),
+            source_dropoff: renderer.source_dropoff(),
+            seed: base.seed(),
+            normalized: base.normalized(),
+            kernel: renderer.kernel().into(),
+            color_space: renderer.color_space().into(),
+            palette: renderer.palette().map(ScenePalette::from),
+            boundary_mode: base.boundary_mode(),
+            loop_period: base.loop_period(),
+            render_scale: renderer.render_scale(),
+            far_field_tolerance: renderer.far_field_tolerance(),
+            sources: base.sources().iter().map(Source::to_scene).collect(),
+        }
+    }
+
+    /// Rebuilds the Spectrum captured by the Scene.
+    ///
+    /// Scenes read with `from_json` or `from_code` always have a valid palette. Otherwise, an invalid palette is left out.
+    #[must_use]
+    pub fn restore(&self) -> (BaseSpectrum, Renderer) {
+        let (canvas_width, canvas_height) =
+            base::canvas_size(self.width, self.height, self.normalized);
+
+        let mut base = BaseSpectrum::from_sources(
+            self.width,
+            self.height,
+            self.movement_speed,
+            self.color_speed,
+            self.seed,
+            self.sources
+                .iter()
+                .map(|source| Source::from_scene(source, canvas_width, canvas_height))
+                .collect(),
+            self.normalized,
+        );
+        base.restore_settings(self.boundary_mode, self.loop_period);
+
+        let mut renderer = Renderer::new(self.source_dropoff);
+        renderer.set_kernel(Kernel::new(self.kernel.kind, Some(self.kernel.parameter)));
+        renderer.set_color_space(ColorSpace::new(
+            self.color_space.kind,
+            Some(self.color_space.lightness),
+            Some(self.color_space.chroma),
+        ));
+        renderer.set_palette(
+            self.palette
+                .as_ref()
+                .and_then(|palette| palette.to_palette().ok()),
+        );
+        renderer.set_render_scale(self.render_scale);
+        renderer.set_far_field_tolerance(self.far_field_tolerance);
+
+        (base, renderer)
+    }
+
+    /// Serializes the Scene to JSON.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the Scene cannot be serialized, which cannot happen for its plain numeric fields.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("scenes always serialize")
+    }
+
+    /// Deserializes a Scene from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON is not a valid scene of the current or an earlier version.
+    pub fn from_json(json: &str) -> Result<Self, SceneError> {
+        let SceneVersion { version } = serde_json::from_str(json)?;
+        if !(SCENE_VERSION_SOURCES..=SCENE_VERSION).contains(&version) {
+            return Err(SceneError::UnsupportedVersion(version));
+        }
+
+        let mut scene: Self = serde_json::from_str(json)?;
+        scene.version = SCENE_VERSION;
+        scene.validate()?;
+
+        Ok(scene)
+    }
+
+    /// Checks that the Scene can be restored as it was captured.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Scene's canvas is empty or has more than `MAX_SCENE_PIXELS` pixels, or its palette is not a valid Palette.
+    pub fn validate(&self) -> Result<(), SceneError> {
+        let pixels = self.width.checked_mul(self.height);
+        if !pixels.is_some_and(|pixels| (1..=MAX_SCENE_PIXELS).contains(&pixels)) {
+            return Err(SceneError::InvalidSize {
+                width: self.width,
+                height: self.height,
+            });
+        }
+
+        if let Some(palette) = &self.palette {
+            palette.to_palette().map_err(SceneError::InvalidPalette)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Returns a Scene with every setting changed from its default, which the scene code tests share.
+    ///
+    /// # Arguments
+    ///
+    /// * `normalized` - whether the Sources' coordinates are normalized.
+    pub fn scene(normalized: bool) -> Scene {
+        let mut base = BaseSpectrum::new(321, 179, 16, 40, 30, Some(42));
+        base.set_normalized(normalized);
+        base.set_boundary_mode(BoundaryMode::Wrap);
+        base.set_loop_period(Some(240));
+        base.tick_by(1.5);
+
+        let mut renderer = Renderer::new(70);
+        renderer.set_kernel(Kernel::new(KernelKind::Gaussian, Some(80.)));
+        renderer.set_color_space(ColorSpace::new(ColorSpaceKind::Oklch, Some(0.6), None));
+        renderer.set_palette(Some(
+            Palette::new(
+                &[0xff_40_00, 0x00_80_ff],
+                Some(&[0., 0.25]),
+                PaletteSpace::LinearRgb,
+            )
+            .unwrap(),
+        ));
+        renderer.set_render_scale(0.5);
+        renderer.set_far_field_tolerance(Some(0.4));
+
+        Scene::capture(&base, &renderer)
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let json = scene(true).to_json();
+        let (base, renderer) = Scene::from_json(&json).unwrap().restore();
+
+        assert_eq!(Scene::capture(&base, &renderer).to_json(), json);
+    }
+
+    #[test]
+    fn json_without_settings_uses_defaults() {
+        let mut scene = scene(true);
+        scene.version = SCENE_VERSION_SOURCES;
+        let mut json: serde_json::Value = serde_json::from_str(&scene.to_json()).unwrap();
+        let fields = json.as_object_mut().unwrap();
+        for field in [
+            "kernel",
+            "colorSpace",
+            "palette",
+            "boundaryMode",
+            "loopPeriod",
+            "renderScale",
+            "farFieldTolerance",
+        ] {
+            fields.remove(field);
+        }
+
+        let scene = Scene::from_json(&json.to_string()).unwrap();
+        let (base, renderer) = scene.restore();
+
+        assert_eq!(scene.version, SCENE_VERSION);
+        assert_eq!(base.boundary_mode(), BoundaryMode::Bounce);
+        assert_eq!(base.loop_period(), None);
+        assert_eq!(renderer.kernel(), Kernel::default());
+        assert!(renderer.palette().is_none());
+        assert!((renderer.render_scale() - 1.).abs() < f32::EPSILON);
+        assert_eq!(renderer.far_field_tolerance(), None);
+    }
+
+    #[test]
+    fn invalid_json_is_rejected() {
+        let with = |edit: fn(&mut Scene)| {
+            let mut scene = scene(true);
+            edit(&mut scene);
+            Scene::from_json(&scene.to_json())
+        };
+
+        assert!(matches!(
+            Scene::from_json("{\"version\": 2"),
+            Err(SceneError::Json(_))
+        ));
+        assert!(matches!(
+            with(|scene| scene.version = SCENE_VERSION + 1),
+            Err(SceneError::UnsupportedVersion(version)) if version == SCENE_VERSION + 1
+        ));
+        assert!(matches!(
+            with(|scene| scene.version = 0),
+            Err(SceneError::UnsupportedVersion(0))
+        ));
+        assert!(matches!(
+            with(|scene| scene.width = 0),
+            Err(SceneError::InvalidSize { width: 0, .. })
+        ));
+        assert!(matches!(
+            with(|scene| (scene.width, scene.height) = (u32::MAX, u32::MAX)),
+            Err(SceneError::InvalidSize { .. })
+        ));
+        assert!(matches!(
+            with(|scene| scene.palette.as_mut().unwrap().colors.clear()),
+            Err(SceneError::InvalidPalette(_))
+        ));
+    }
+}
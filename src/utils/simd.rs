@@ -0,0 +1,264 @@
+use std::f32::consts;
+use wide::f32x8;
+
+use crate::utils::base::Hue;
+use crate::utils::color::{ColorSpace, ColorSpaceKind, LAB_TO_LMS, LMS_TO_RGB};
+use crate::utils::kernel::{self, KernelKind};
+use crate::utils::render::{Renderer, SourceInfo};
+
+const TWO_PI: f32 = consts::PI * 2_f32;
+
+/// The number of pixels drawn at once.
+const LANES: usize = 8;
+
+/// The x-offset of each lane's pixel from the first.
+const LANE_OFFSETS: [f32; LANES] = [0., 1., 2., 3., 4., 5., 6., 7.];
+
+/// Draws a single row of pixels, `LANES` pixels at a time, using the `Renderer`'s settings.
+///
+/// Matches `Renderer::draw_scalar` to within rounding, as the vectorized `exp` and `powf` are approximations.
+///
+/// # Arguments
+///
+/// * `renderer` - the `Renderer` whose settings are used.
+/// * `sources` - the position and Hue components of each Source, in reference pixels.
+/// * `y_float` - the y-coordinate of the row, in reference pixels.
+/// * `pixel_size` - the size of a pixel, in reference pixels.
+/// * `row` - the row's pixel data.
+pub fn draw_row(
+    renderer: &Renderer,
+    sources: &[SourceInfo],
+    y_float: f32,
+    pixel_size: f32,
+    row: &mut [u8],
+) {
+    let parameter = renderer.kernel().parameter();
+
+    // Dispatch once per row rather than once per pixel and Source.
+    match renderer.kernel().kind() {
+        KernelKind::InversePower if (parameter - 2.).abs() < f32::EPSILON => {
+            draw_row_with(renderer, sources, y_float, pixel_size, row, inverse_square);
+        }
+        KernelKind::InversePower => {
+            let half_exponent = f32x8::splat(parameter / 2.);
+            draw_row_with(renderer, sources, y_float, pixel_size, row, |dist_sq| {
+                inverse_power(dist_sq, half_exponent)
+            });
+        }
+        KernelKind::Gaussian => {
+            let scale = f32x8::splat(kernel::gaussian_scale(parameter));
+            draw_row_with(renderer, sources, y_float, pixel_size, row, |dist_sq| {
+                (dist_sq * scale).exp()
+            });
+        }
+        KernelKind::Exponential => {
+            let inverse_scale = f32x8::splat(parameter.recip());
+            draw_row_with(renderer, sources, y_float, pixel_size, row, |dist_sq| {
+                (-dist_sq.sqrt() * inverse_scale).exp()
+            });
+        }
+        KernelKind::SmoothCompact => {
+            let inverse_radius_sq = f32x8::splat(parameter.powi(2).recip());
+            draw_row_with(renderer, sources, y_float, pixel_size, row, |dist_sq| {
+                let falloff = dist_sq
+                    .mul_neg_add(inverse_radius_sq, f32x8::ONE)
+                    .max(f32x8::ZERO);
+                falloff * falloff
+            });
+        }
+    }
+}
+
+/// Draws a single row of pixels, `LANES` pixels at a time, weighting each Source with `weight`.
+///
+/// # Arguments
+///
+/// * `renderer` - the `Renderer` whose settings are used.
+/// * `sources` - the position and Hue components of each Source, in reference pixels.
+/// * `y_float` - the y-coordinate of the row, in reference pixels.
+/// * `pixel_size` - the size of a pixel, in reference pixels.
+/// * `row` - the row's pixel data.
+/// * `weight` - returns the weight of a Source given its squared distance from each pixel.
+fn draw_row_with<W: Fn(f32x8) -> f32x8>(
+    renderer: &Renderer,
+    sources: &[SourceInfo],
+    y_float: f32,
+    pixel_size: f32,
+    row: &mut [u8],
+    weight: W,
+) {
+    let lane_offsets = f32x8::from(LANE_OFFSETS);
+    let pixel_size_lanes = f32x8::splat(pixel_size);
+    let source_dropoff_exponent = renderer.source_dropoff_exponent();
+
+    for (chunk_index, pixels) in row.chunks_mut(LANES * 4).enumerate() {
+        let x_float =
+            (f32x8::splat((chunk_index * LANES) as f32) + lane_offsets) * pixel_size_lanes;
+        let mut dist_factor_inverse_sum = f32x8::ZERO;
+        let mut hue_vector_cos = f32x8::ZERO;
+        let mut hue_vector_sin = f32x8::ZERO;
+
+        for &(source_x, source_y, hue_cos, hue_sin) in sources {
+            let x_diff = x_float - f32x8::splat(source_x);
+            let y_diff = y_float - source_y;
+
+            let dist_factor_inverse = weight(x_diff.mul_add(x_diff, f32x8::splat(y_diff * y_diff)));
+            dist_factor_inverse_sum += dist_factor_inverse;
+
+            hue_vector_cos = f32x8::splat(hue_cos).mul_add(dist_factor_inverse, hue_vector_cos);
+            hue_vector_sin = f32x8::splat(hue_sin).mul_add(dist_factor_inverse, hue_vector_sin);
+        }
+
+        let alpha = powf(
+            dist_factor_inverse_sum.min(f32x8::ONE),
+            source_dropoff_exponent,
+        ) * f32x8::splat(f32::from(u8::MAX));
+
+        let hue = atan2_approx(hue_vector_cos, hue_vector_sin);
+        let [r, g, b] = renderer.palette().map_or_else(
+            || hue_to_rgb(renderer.color_space(), hue),
+            |palette| {
+                let hue = hue.to_array();
+                let mut channels = [[0_f32; LANES]; 3];
+
+                for (lane, &hue) in hue.iter().enumerate() {
+                    let rgb = palette.sample(Hue::new(hue));
+                    channels[0][lane] = f32::from(rgb.0);
+                    channels[1][lane] = f32::from(rgb.1);
+                    channels[2][lane] = f32::from(rgb.2);
+                }
+
+                channels.map(f32x8::from)
+            },
+        );
+
+        let [r, g, b, alpha] = [r, g, b, alpha].map(f32x8::to_array);
+
+        for (lane, pixel) in pixels.chunks_exact_mut(4).enumerate() {
+            pixel.copy_from_slice(&[
+                r[lane] as u8,
+                g[lane] as u8,
+                b[lane] as u8,
+                alpha[lane] as u8,
+            ]);
+        }
+    }
+}
+
+/// Returns the inverse square weight of each pixel, like `kernel::inverse_square`.
+fn inverse_square(dist_sq: f32x8) -> f32x8 {
+    f32x8::ONE / (dist_sq + f32x8::ONE)
+}
+
+/// Returns the inverse power weight of each pixel, like `kernel::inverse_power`.
+fn inverse_power(dist_sq: f32x8, half_exponent: f32x8) -> f32x8 {
+    f32x8::ONE / (dist_sq.powf_simd(half_exponent) + f32x8::ONE)
+}
+
+/// Raises each lane to a non-negative power, where zero to any positive power is zero.
+///
+/// # Arguments
+///
+/// * `base` - the non-negative bases.
+/// * `exponent` - the exponent.
+fn powf(base: f32x8, exponent: f32) -> f32x8 {
+    if exponent == 0. {
+        return f32x8::ONE;
+    }
+
+    base.simd_gt(f32x8::ZERO)
+        .bitselect(base.powf_simd(f32x8::splat(exponent)), f32x8::ZERO)
+}
+
+/// Approximates the arctangent of each lane, like `math::atan_approx`.
+fn atan_approx(quotient: f32x8) -> f32x8 {
+    f32x8::splat(0.273).mul_add(f32x8::ONE - quotient.abs(), f32x8::splat(consts::FRAC_PI_4))
+        * quotient
+}
+
+/// Calculates the arctangent of each lane from its cosine and sine, like `math::atan2_approx`.
+///
+/// # Parameters
+///
+/// * `cos` - the cosine/x terms.
+/// * `sin` - the sine/y terms.
+fn atan2_approx(cos: f32x8, sin: f32x8) -> f32x8 {
+    let sin_negative = sin.simd_lt(f32x8::ZERO);
+
+    let cos_major_offset = cos.simd_lt(f32x8::ZERO).bitselect(
+        f32x8::splat(consts::PI),
+        sin_negative.bitselect(f32x8::splat(TWO_PI), f32x8::ZERO),
+    );
+    let sin_major_offset = sin_negative.bitselect(
+        f32x8::splat(3. * consts::FRAC_PI_2),
+        f32x8::splat(consts::FRAC_PI_2),
+    );
+
+    cos.abs().simd_gt(sin.abs()).bitselect(
+        atan_approx(sin / cos) + cos_major_offset,
+        sin_major_offset - atan_approx(cos / sin),
+    )
+}
+
+/// Converts each lane's Hue to RGB channels in [0, 255] in a `ColorSpace`, like `ColorSpace::to_rgb`.
+fn hue_to_rgb(color_space: ColorSpace, hue: f32x8) -> [f32x8; 3] {
+    let lightness = f32x8::splat(color_space.lightness());
+
+    let channels = match color_space.kind() {
+        ColorSpaceKind::Hsl => {
+            let chroma = f32x8::splat(
+                (1. - 2_f32.mul_add(color_space.lightness(), -1.).abs()) * color_space.chroma(),
+            );
+            pure_hue(hue).map(|channel| chroma.mul_add(channel - f32x8::HALF, lightness))
+        }
+        ColorSpaceKind::Hsv => {
+            let chroma = f32x8::splat(color_space.lightness() * color_space.chroma());
+            pure_hue(hue).map(|channel| chroma.mul_add(channel - f32x8::ONE, lightness))
+        }
+        ColorSpaceKind::Oklch => oklch_to_srgb(lightness, f32x8::splat(color_space.chroma()), hue),
+    };
+
+    channels.map(|channel| channel * f32x8::splat(f32::from(u8::MAX)))
+}
+
+/// Returns the fully saturated RGB channels of each lane's Hue, each in [0, 1].
+fn pure_hue(hue: f32x8) -> [f32x8; 3] {
+    let two_pi = f32x8::splat(TWO_PI);
+    let six = f32x8::splat(6.);
+    let three = f32x8::splat(3.);
+
+    let sextant = (hue - (hue / two_pi).floor() * two_pi) / f32x8::splat(consts::FRAC_PI_3);
+
+    [0., 4., 2.].map(|offset| {
+        let position = sextant + f32x8::splat(offset);
+        ((position - (position / six).floor() * six - three).abs() - f32x8::ONE)
+            .clamp(f32x8::ZERO, f32x8::ONE)
+    })
+}
+
+/// Converts each lane's OKLCH color to gamma-encoded sRGB channels, each clipped to [0, 1], like `color::oklch_to_srgb`.
+fn oklch_to_srgb(lightness: f32x8, chroma: f32x8, hue: f32x8) -> [f32x8; 3] {
+    let (sin, cos) = hue.sin_cos();
+    let lms = transform(LAB_TO_LMS, [lightness, chroma * cos, chroma * sin])
+        .map(|cone| cone * cone * cone);
+
+    transform(LMS_TO_RGB, lms).map(|channel| linear_to_srgb(channel.clamp(f32x8::ZERO, f32x8::ONE)))
+}
+
+/// Multiplies a 3x3 matrix by each lane's vector.
+fn transform(matrix: [[f32; 3]; 3], vector: [f32x8; 3]) -> [f32x8; 3] {
+    matrix.map(|row| {
+        f32x8::splat(row[2]).mul_add(
+            vector[2],
+            f32x8::splat(row[1]).mul_add(vector[1], f32x8::splat(row[0]) * vector[0]),
+        )
+    })
+}
+
+/// Applies the sRGB transfer function to each lane's linear channel in [0, 1], like `color::linear_to_srgb`.
+fn linear_to_srgb(linear: f32x8) -> f32x8 {
+    linear.simd_lt(f32x8::splat(0.003_130_8)).bitselect(
+        linear * f32x8::splat(12.92),
+        f32x8::splat(1.055).mul_add(powf(linear, 2.4_f32.recip()), f32x8::splat(-0.055)),
+    )
+}
@@ -0,0 +1,317 @@
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// The smallest fraction of the canvas resolution a Spectrum can be evaluated at.
+pub const MIN_RENDER_SCALE: f32 = 0.25;
+
+/// The GLSL ES 1.0 fragment shader upsampling the `frame` texture to the canvas with the same samples and Catmull-Rom interpolation as `upsample`.
+///
+/// The texture holds `scaled_len(width, render_scale)` by `scaled_len(height, render_scale)` samples, with its first row at the bottom, and must be read with `NEAREST` filtering so that each of the 16 taps reads a single texel.
+pub const GLSL_ES_1: &str = r"
+    precision highp float;
+
+    uniform sampler2D frame;
+    uniform vec2 frame_size;
+    uniform float render_scale;
+    uniform float height;
+
+    vec4 catmull_rom(float t) {
+        float t_sq = t * t;
+        return 0.5 * vec4(
+            t * (2.0 * t - t_sq - 1.0),
+            t_sq * (3.0 * t - 5.0) + 2.0,
+            t * (t * (4.0 - 3.0 * t) + 1.0),
+            t_sq * (t - 1.0)
+        );
+    }
+
+    void main() {
+        // Like `upsample`, pixel (x, y), counting rows down from the top, lies at (x, y) * render_scale among the samples.
+        vec2 position = vec2(gl_FragCoord.x - 0.5, height - 0.5 - gl_FragCoord.y) * render_scale;
+        vec2 texel = floor(position);
+        vec4 x_weights = catmull_rom(position.x - texel.x);
+        vec4 y_weights = catmull_rom(position.y - texel.y);
+        vec4 color = vec4(0.0);
+
+        for (int j = 0; j < 4; j++) {
+            for (int i = 0; i < 4; i++) {
+                vec2 tap = clamp(texel + vec2(float(i - 1), float(j - 1)), vec2(0.0), frame_size - 1.0);
+                vec2 coords = (vec2(tap.x, frame_size.y - 1.0 - tap.y) + 0.5) / frame_size;
+                color += x_weights[i] * y_weights[j] * texture2D(frame, coords);
+            }
+        }
+
+        gl_FragColor = clamp(color, 0.0, 1.0);
+    }
+";
+
+/// The GLSL ES 3.0 fragment shader upsampling the `frame` texture to the canvas, like `GLSL_ES_1`.
+pub const GLSL_ES_3: &str = r"#version 300 es
+    precision highp float;
+
+    uniform sampler2D frame;
+    uniform float render_scale;
+    uniform float height;
+
+    out vec4 frag_color;
+
+    vec4 catmull_rom(float t) {
+        float t_sq = t * t;
+        return 0.5 * vec4(
+            t * (2.0 * t - t_sq - 1.0),
+            t_sq * (3.0 * t - 5.0) + 2.0,
+            t * (t * (4.0 - 3.0 * t) + 1.0),
+            t_sq * (t - 1.0)
+        );
+    }
+
+    void main() {
+        vec2 position = vec2(gl_FragCoord.x - 0.5, height - 0.5 - gl_FragCoord.y) * render_scale;
+        vec2 texel = floor(position);
+        vec4 x_weights = catmull_rom(position.x - texel.x);
+        vec4 y_weights = catmull_rom(position.y - texel.y);
+        ivec2 max_texel = textureSize(frame, 0) - 1;
+        vec4 color = vec4(0.0);
+
+        for (int j = 0; j < 4; j++) {
+            for (int i = 0; i < 4; i++) {
+                ivec2 tap = clamp(ivec2(texel) + ivec2(i - 1, j - 1), ivec2(0), max_texel);
+                color += x_weights[i] * y_weights[j] * texelFetch(frame, ivec2(tap.x, max_texel.y - tap.y), 0);
+            }
+        }
+
+        frag_color = clamp(color, 0.0, 1.0);
+    }
+";
+
+/// The indices of the four samples blended into a pixel along one axis, and their weights.
+type Taps = ([usize; 4], [f32; 4]);
+
+/// Returns the number of samples needed along an axis so that, `1 / render_scale` pixels apart, they span every pixel.
+///
+/// # Arguments
+///
+/// * `len` - the number of pixels along the axis.
+/// * `render_scale` - the fraction of the resolution the samples are taken at.
+#[must_use]
+pub fn scaled_len(len: usize, render_scale: f32) -> usize {
+    if len == 0 {
+        return 0;
+    }
+
+    ((len - 1) as f32 * render_scale).ceil() as usize + 1
+}
+
+/// Upsamples RGBA pixel data using Catmull-Rom bicubic interpolation, which keeps gradients smooth without blurring them.
+///
+/// Sample `(i, j)` of `samples` lies on pixel `(i / render_scale, j / render_scale)` of `data`.
+///
+/// With the `parallel` feature, rows are split across threads.
+///
+/// # Arguments
+///
+/// * `samples` - the pixel data to upsample, `scaled_len(width, render_scale)` by `scaled_len(height, render_scale)` pixels.
+/// * `render_scale` - the fraction of the resolution `samples` were taken at.
+/// * `data` - the pixel data to draw into.
+/// * `width` - the width of `data`.
+/// * `height` - the height of `data`.
+///
+/// # Panics
+///
+/// Panics if `data` is smaller than `width * height * 4` bytes, or `samples` is smaller than its size.
+pub fn upsample(samples: &[u8], render_scale: f32, data: &mut [u8], width: usize, height: usize) {
+    let sample_width = scaled_len(width, render_scale);
+    let sample_height = scaled_len(height, render_scale);
+    assert!(
+        data.len() >= width * height * 4,
+        "pixel buffer is too small for a {width}x{height} Spectrum"
+    );
+    assert!(
+        samples.len() >= sample_width * sample_height * 4,
+        "sample buffer is too small for {sample_width}x{sample_height} samples"
+    );
+
+    let columns: Vec<Taps> = (0..width)
+        .map(|x| get_taps(x, render_scale, sample_width))
+        .collect();
+    let rows: Vec<Taps> = (0..height)
+        .map(|y| get_taps(y, render_scale, sample_height))
+        .collect();
+
+    // Interpolating each row of samples across first leaves four values to blend per pixel rather than sixteen.
+    let mut intermediate = vec![0_f32; sample_height * width * 4];
+    for (sample_row, row) in samples
+        .chunks_exact(sample_width * 4)
+        .zip(intermediate.chunks_exact_mut(width * 4))
+    {
+        for (pixel, (indices, weights)) in row.chunks_exact_mut(4).zip(&columns) {
+            for (channel, value) in pixel.iter_mut().enumerate() {
+                *value = blend(weights, |tap| {
+                    f32::from(sample_row[indices[tap] * 4 + channel])
+                });
+            }
+        }
+    }
+
+    let upsample_row = |(y, row): (usize, &mut [u8])| {
+        let (indices, weights) = &rows[y];
+        let intermediate_rows =
+            indices.map(|index| &intermediate[index * width * 4..][..width * 4]);
+
+        for (i, value) in row.iter_mut().enumerate() {
+            // Rounds to the nearest value, as casting saturates to [0, 255].
+            *value = (blend(weights, |tap| intermediate_rows[tap][i]) + 0.5) as u8;
+        }
+    };
+
+    #[cfg(feature = "parallel")]
+    data.par_chunks_exact_mut(width * 4)
+        .take(height)
+        .enumerate()
+        .for_each(upsample_row);
+
+    #[cfg(not(feature = "parallel"))]
+    data.chunks_exact_mut(width * 4)
+        .take(height)
+        .enumerate()
+        .for_each(upsample_row);
+}
+
+/// Returns the samples blended into a pixel along one axis, and their Catmull-Rom weights.
+///
+/// Samples beyond either end of the axis are clamped to the nearest one.
+///
+/// # Arguments
+///
+/// * `index` - the index of the pixel along the axis.
+/// * `render_scale` - the fraction of the resolution the samples were taken at.
+/// * `len` - the number of samples along the axis.
+fn get_taps(index: usize, render_scale: f32, len: usize) -> Taps {
+    let position = index as f32 * render_scale;
+    let start = position.floor();
+    let t = position - start;
+    let start = start as usize;
+
+    (
+        [0, 1, 2, 3].map(|offset| (start + offset).saturating_sub(1).min(len - 1)),
+        [
+            t * t.mul_add(2. - t, -1.) / 2.,
+            (t * t).mul_add(3_f32.mul_add(t, -5.), 2.) / 2.,
+            t * t.mul_add(3_f32.mul_add(-t, 4.), 1.) / 2.,
+            t * t * (t - 1.) / 2.,
+        ],
+    )
+}
+
+/// Returns the weighted sum of four samples.
+///
+/// # Arguments
+///
+/// * `weights` - the weight of each sample.
+/// * `sample` - returns the value of a sample given its position among the four.
+fn blend<S: Fn(usize) -> f32>(weights: &[f32; 4], sample: S) -> f32 {
+    // `mul_add` is a slow library call on targets without fused multiply-add instructions.
+    #[allow(clippy::suboptimal_flops)]
+    weights
+        .iter()
+        .enumerate()
+        .fold(0., |sum, (tap, &weight)| sum + weight * sample(tap))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Render scales which do not divide the sizes below, so the last sample lies past the last pixel.
+    const RENDER_SCALES: [f32; 5] = [MIN_RENDER_SCALE, 0.3, 0.45, 0.7, 0.99];
+
+    const SIZES: [(usize, usize); 4] = [(1, 1), (10, 7), (33, 16), (64, 41)];
+
+    /// Returns RGBA samples whose channels vary independently across the grid.
+    fn gradient(width: usize, height: usize) -> Vec<u8> {
+        (0..height)
+            .flat_map(|y| (0..width).flat_map(move |x| [x * 7, y * 5, x * 3 + y * 2, 255]))
+            .map(|value| (value % 256) as u8)
+            .collect()
+    }
+
+    #[test]
+    fn full_scale_copies_samples() {
+        for (width, height) in SIZES {
+            let samples = gradient(width, height);
+            let mut data = vec![0; width * height * 4];
+            upsample(&samples, 1., &mut data, width, height);
+
+            assert_eq!(data, samples);
+        }
+    }
+
+    #[test]
+    fn scaled_samples_span_every_pixel() {
+        for render_scale in RENDER_SCALES {
+            for (width, _) in SIZES {
+                let len = scaled_len(width, render_scale);
+                let last = (width - 1) as f32 * render_scale;
+
+                assert!(len >= 1);
+                assert!((len - 1) as f32 >= last, "{width} pixels at {render_scale}");
+                assert!(len == 1 || ((len - 2) as f32) < last);
+            }
+        }
+    }
+
+    #[test]
+    fn taps_are_clamped_to_the_samples() {
+        for render_scale in RENDER_SCALES {
+            for (width, _) in SIZES {
+                let len = scaled_len(width, render_scale);
+
+                for x in 0..width {
+                    let (indices, weights) = get_taps(x, render_scale, len);
+
+                    assert!(indices.iter().all(|&index| index < len));
+                    assert!(indices.windows(2).all(|pair| pair[0] <= pair[1]));
+                    assert!((weights.iter().sum::<f32>() - 1.).abs() < 1e-5);
+                }
+            }
+        }
+
+        // The first tap of the first pixel lies before the first sample, and the last taps of the last pixel after the last.
+        assert_eq!(get_taps(0, 0.5, 5).0, [0, 0, 1, 2]);
+        assert_eq!(get_taps(8, 0.5, 5).0, [3, 4, 4, 4]);
+    }
+
+    #[test]
+    fn uneven_scales_keep_flat_colors_flat() {
+        for render_scale in RENDER_SCALES {
+            for (width, height) in SIZES {
+                let samples = [12, 34, 56, 255]
+                    .repeat(scaled_len(width, render_scale) * scaled_len(height, render_scale));
+                let mut data = vec![0; width * height * 4];
+                upsample(&samples, render_scale, &mut data, width, height);
+
+                assert!(data.chunks_exact(4).all(|pixel| pixel == [12, 34, 56, 255]));
+            }
+        }
+    }
+
+    #[test]
+    fn pixels_on_samples_keep_their_values() {
+        for (width, height) in SIZES {
+            let render_scale = 0.5;
+            let sample_width = scaled_len(width, render_scale);
+            let samples = gradient(sample_width, scaled_len(height, render_scale));
+            let mut data = vec![0; width * height * 4];
+            upsample(&samples, render_scale, &mut data, width, height);
+
+            for y in (0..height).step_by(2) {
+                for x in (0..width).step_by(2) {
+                    let pixel = (y * width + x) * 4;
+                    let sample = (y / 2 * sample_width + x / 2) * 4;
+
+                    assert_eq!(data[pixel..pixel + 4], samples[sample..sample + 4]);
+                }
+            }
+        }
+    }
+}
@@ -22,6 +22,13 @@ module.exports = {
     filename: "startup.js",
   },
   mode: "production",
+  devServer: {
+    // Cross-origin isolation lets a `wasm-threads` build share memory with its Web Workers.
+    headers: {
+      "Cross-Origin-Opener-Policy": "same-origin",
+      "Cross-Origin-Embedder-Policy": "require-corp",
+    },
+  },
   plugins: [
     new CopyWebpackPlugin({
       patterns: [